@@ -21,11 +21,51 @@
 //! > REMINDER: you may _not_ search for an algorithm in Rust for reference or to depend on here,
 //! > per the honor code!
 
-// NOTE: you may use any data structure you like from `std::collections`
-use std::collections::{HashMap, HashSet};
+pub mod all_pairs;
+pub mod astar;
+pub mod bidirectional;
+pub mod constrained;
+pub mod contraction;
+pub mod cost;
+pub mod error;
+pub mod explain;
+pub mod export;
+pub mod itinerary;
+pub mod k_shortest;
+pub mod loader;
+pub mod network;
+pub mod pareto;
+pub mod party;
+pub mod query;
+pub mod reachability;
+pub mod schedule;
+pub mod signed;
+pub mod terrain_kind;
+pub mod terrain_profile;
+pub mod validation;
+
+pub use all_pairs::{AllPairsAlgorithm, CostMatrix};
+pub use astar::{AStarOutcome, Coordinates, Heuristic, StraightLine};
+pub use constrained::Budget;
+pub use contraction::ContractionHierarchy;
+pub use cost::{Cost, CostFunction, CostOverflow};
+pub use error::{ParseError, UnencodableCampsite};
+pub use explain::{RejectedTrail, Rejection, RouteExplanation, TrailBreakdown};
+pub use itinerary::{Itinerary, WaypointOrder, EXACT_WAYPOINT_LIMIT};
+pub use loader::{LoadError, MalformedLine};
+pub use network::TrailNetwork;
+pub use party::Party;
+pub use query::{Objective, RouteQuery};
+pub use schedule::{Journey, Schedule, Window};
+pub use signed::NegativeCycle;
+pub use terrain_kind::{Discipline, RegisterError, TerrainKind, TerrainRules};
+pub use terrain_profile::{Rate, TerrainProfile};
+pub use validation::{Problem, Severity, ValidationReport};
+
+use std::fmt;
 
 /// Various types of terrain that may be encountered while traversing the trail network.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Terrain {
 	PavedTrail,
 	UnpavedTrail,
@@ -33,13 +73,15 @@ pub enum Terrain {
 	Water,
 	Zipline,
 	RopeBridge,
+	/// A kind of terrain registered with [`Terrain::register`].
+	Custom(&'static TerrainKind),
 }
 
 impl TryFrom<String> for Terrain {
-	type Error = ();
+	type Error = ParseError;
 
 	/// OPTIONAL
-	fn try_from(s: String) -> Result<Terrain, ()> {
+	fn try_from(s: String) -> Result<Terrain, ParseError> {
 		match s.as_str() {
 			"PavedTrail" => Ok(Terrain::PavedTrail),
 			"UnpavedTrail" => Ok(Terrain::UnpavedTrail),
@@ -47,11 +89,26 @@ impl TryFrom<String> for Terrain {
 			"Water" => Ok(Terrain::Water),
 			"Zipline" => Ok(Terrain::Zipline),
 			"RopeBridge" => Ok(Terrain::RopeBridge),
-			_ => Err(()),
+			_ => Terrain::registered(&s).ok_or(ParseError::UnknownTerrain { offset: 0, name: s }),
 		}
 	}
 }
 
+impl fmt::Display for Terrain {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = match self {
+			Terrain::PavedTrail => "PavedTrail",
+			Terrain::UnpavedTrail => "UnpavedTrail",
+			Terrain::RockyTrail => "RockyTrail",
+			Terrain::Water => "Water",
+			Terrain::Zipline => "Zipline",
+			Terrain::RopeBridge => "RopeBridge",
+			Terrain::Custom(kind) => &kind.name,
+		};
+		f.write_str(name)
+	}
+}
+
 /// A level of skill that a hiker may attain in various forms of hiking.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Skill {
@@ -61,21 +118,32 @@ pub enum Skill {
 }
 
 impl TryFrom<String> for Skill {
-	type Error = ();
+	type Error = ParseError;
 
 	/// OPTIONAL
-	fn try_from(s: String) -> Result<Skill, ()> {
+	fn try_from(s: String) -> Result<Skill, ParseError> {
 		// String encodings of skill variants are just their names.
 		// This problem is OPTIONAL
 		match s.as_str() {
 			"Beginner" => Ok(Skill::Beginner),
 			"Intermediate" => Ok(Skill::Intermediate),
 			"Expert" => Ok(Skill::Expert),
-			_ => Err(()),
+			_ => Err(ParseError::UnknownSkill { offset: 0, name: s }),
 		}
 	}
 }
 
+impl fmt::Display for Skill {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = match self {
+			Skill::Beginner => "Beginner",
+			Skill::Intermediate => "Intermediate",
+			Skill::Expert => "Expert",
+		};
+		f.write_str(name)
+	}
+}
+
 /// A one-way trail that can be traveled from the starting campsite to the ending campsite.
 /// Every campsite in the network has a unique name.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -95,13 +163,29 @@ pub struct Trail {
 	pub danger: u8,
 }
 
+/// Returns the part of `s` between the byte offsets `from` and `to` with surrounding whitespace
+/// trimmed, along with the byte offset where the trimmed part starts.
+fn trimmed_field(s: &str, from: usize, to: usize) -> (usize, &str) {
+	let field = &s[from..to];
+	let leading_whitespace = field.len() - field.trim_start().len();
+	(from + leading_whitespace, field.trim())
+}
+
+/// Returns the byte offset of the first `delimiter` in `s` at or after `from`.
+fn find_delimiter(s: &str, delimiter: &'static str, from: usize) -> Result<usize, ParseError> {
+	s[from..]
+		.find(delimiter)
+		.map(|position| from + position)
+		.ok_or(ParseError::MissingDelimiter { offset: s.len(), delimiter })
+}
+
 // Parse trail data from a string.
 // It is recommended to use your implementation for Terrain as a helper here.
 impl TryFrom<String> for Trail {
-	type Error = ();
+	type Error = ParseError;
 
 	/// OPTIONAL
-	fn try_from(s: String) -> Result<Trail, ()> {
+	fn try_from(s: String) -> Result<Trail, ParseError> {
 		// The encoding of trail information is as follows:
 		//
 		// Starting Site => Ending Site: Distance (Terrain) [Danger]
@@ -109,33 +193,89 @@ impl TryFrom<String> for Trail {
 		// ### Examples:
 		// Mountain Top => Green Lake: 2000 (PavedTrail) [19]
 		// The Bird Watch => Lost Colony: 400 (Zipline) [20]
-		let start = s.split("=>").next().ok_or(())?.trim().to_string();
-		let end = s.split("=>").nth(1).ok_or(())?.split(":").next().ok_or(())?.trim().to_string();
-		let distance: u32 = s
-			.split(":")
-			.nth(1)
-			.ok_or(())?
-			.split("(")
-			.next()
-			.ok_or(())?
-			.trim()
-			.parse()
-			.map_err(|_| ())?;
-		let terrain = Terrain::try_from(
-			s.split("(").nth(1).ok_or(())?.split(")").next().ok_or(())?.trim().to_string(),
-		)?;
-		let danger: u8 = s
-			.split("[")
-			.nth(1)
-			.ok_or(())?
-			.split("]")
-			.next()
-			.ok_or(())?
-			.trim()
+		let arrow = find_delimiter(&s, "=>", 0)?;
+		let colon = find_delimiter(&s, ":", arrow)?;
+		let open_paren = find_delimiter(&s, "(", colon)?;
+		let close_paren = find_delimiter(&s, ")", open_paren)?;
+		let open_bracket = find_delimiter(&s, "[", close_paren)?;
+		let close_bracket = find_delimiter(&s, "]", open_bracket)?;
+
+		let (offset, start) = trimmed_field(&s, 0, arrow);
+		if start.is_empty() {
+			return Err(ParseError::EmptyCampsite { offset })
+		}
+
+		let (offset, end) = trimmed_field(&s, arrow + 2, colon);
+		if end.is_empty() {
+			return Err(ParseError::EmptyCampsite { offset })
+		}
+
+		let (offset, distance) = trimmed_field(&s, colon + 1, open_paren);
+		let distance: u32 = distance
 			.parse()
-			.map_err(|_| ())?;
+			.map_err(|_| ParseError::InvalidDistance { offset, value: distance.to_string() })?;
+
+		let (offset, terrain) = trimmed_field(&s, open_paren + 1, close_paren);
+		let terrain =
+			Terrain::try_from(terrain.to_string()).map_err(|error| error.offset_by(offset))?;
+
+		let (offset, danger) = trimmed_field(&s, open_bracket + 1, close_bracket);
+		let danger: u8 = danger.parse().map_err(|error: std::num::ParseIntError| {
+			let value = danger.to_string();
+			match error.kind() {
+				std::num::IntErrorKind::PosOverflow =>
+					ParseError::DangerOutOfRange { offset, value },
+				_ => ParseError::InvalidDanger { offset, value },
+			}
+		})?;
 
-		Ok(Trail { start, end, distance, terrain, danger })
+		Ok(Trail { start: start.to_string(), end: end.to_string(), distance, terrain, danger })
+	}
+}
+
+impl Trail {
+	/// Returns whether `name` can be written as a campsite in the text encoding of trails and
+	/// parsed back unchanged.
+	///
+	/// Such names are not empty, have no surrounding whitespace and no line breaks, contain neither
+	/// `=>` nor `:`, and do not start with `#`, which would turn a trail network line into a
+	/// comment.
+	pub fn is_encodable_campsite(name: &str) -> bool {
+		!name.is_empty() &&
+			name.trim() == name &&
+			!name.contains(['\n', '\r', ':']) &&
+			!name.contains("=>") &&
+			!name.starts_with('#')
+	}
+
+	/// Returns whether both of the trail's campsite names can be encoded, see
+	/// [`Trail::is_encodable_campsite`].
+	pub fn is_encodable(&self) -> bool {
+		Trail::is_encodable_campsite(&self.start) && Trail::is_encodable_campsite(&self.end)
+	}
+
+	/// Writes the trail in the encoding it is parsed from, like its `Display` implementation, but
+	/// refuses to write campsite names that would not parse back unchanged.
+	pub fn encode(&self) -> Result<String, UnencodableCampsite> {
+		match [&self.start, &self.end]
+			.into_iter()
+			.find(|name| !Trail::is_encodable_campsite(name))
+		{
+			Some(name) => Err(UnencodableCampsite { name: name.clone() }),
+			None => Ok(self.to_string()),
+		}
+	}
+}
+
+// Write trail data in the same encoding it is parsed from.
+// Names are written as they are, so use `Trail::encode` when the output must parse back.
+impl fmt::Display for Trail {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{} => {}: {} ({}) [{}]",
+			self.start, self.end, self.distance, self.terrain, self.danger
+		)
 	}
 }
 
@@ -166,28 +306,69 @@ impl Default for Hiker {
 
 /// OPTIONAL
 impl TryFrom<String> for Hiker {
-	type Error = ();
+	type Error = ParseError;
 
 	/// The format for a hiker is a simple comma separated list of keys and values.
 	/// The keys MUST be in the right order or the string is invalid.
 	///
 	/// Example:
 	/// "hiking: Beginner, swimming: Intermediate, strong: false, brave: false"
-	fn try_from(value: String) -> Result<Self, ()> {
-		fn get_value_after_colon(s: &str) -> Result<String, ()> {
-			Ok(s.split(":").nth(1).ok_or(())?.trim().to_string())
-		}
+	fn try_from(value: String) -> Result<Self, ParseError> {
+		// Pair every comma separated field with its byte offset
+		let mut fields = value.split(',').scan(0, |next_offset, field| {
+			let offset = *next_offset;
+			*next_offset += field.len() + 1;
+			Some((offset, field))
+		});
+
+		// Returns the value of the next field, after checking that its key is the expected one
+		let mut value_of = |expected: &'static str| -> Result<(usize, &str), ParseError> {
+			let (offset, field) =
+				fields.next().ok_or(ParseError::MissingKey { offset: value.len(), expected })?;
+			let colon = find_delimiter(field, ":", 0).map_err(|error| error.offset_by(offset))?;
+
+			let (key_offset, key) = trimmed_field(field, 0, colon);
+			if key != expected {
+				return Err(ParseError::UnexpectedKey {
+					offset: offset + key_offset,
+					expected,
+					found: key.to_string(),
+				})
+			}
 
-		let mut values = value.split(",");
-		let hiking = Skill::try_from(get_value_after_colon(values.next().ok_or(())?)?)?;
-		let swimming = Skill::try_from(get_value_after_colon(values.next().ok_or(())?)?)?;
-		let strong = get_value_after_colon(values.next().ok_or(())?)?.parse().map_err(|_| ())?;
-		let brave = get_value_after_colon(values.next().ok_or(())?)?.parse().map_err(|_| ())?;
+			let (value_offset, value) = trimmed_field(field, colon + 1, field.len());
+			Ok((offset + value_offset, value))
+		};
+		let parse_bool = |(offset, value): (usize, &str)| {
+			value
+				.parse()
+				.map_err(|_| ParseError::InvalidBool { offset, value: value.to_string() })
+		};
+
+		let (offset, hiking) = value_of("hiking")?;
+		let hiking =
+			Skill::try_from(hiking.to_string()).map_err(|error| error.offset_by(offset))?;
+		let (offset, swimming) = value_of("swimming")?;
+		let swimming =
+			Skill::try_from(swimming.to_string()).map_err(|error| error.offset_by(offset))?;
+		let strong = parse_bool(value_of("strong")?)?;
+		let brave = parse_bool(value_of("brave")?)?;
 
 		Ok(Hiker { hiking, swimming, strong, brave })
 	}
 }
 
+// Write hikers in the same encoding they are parsed from.
+impl fmt::Display for Hiker {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"hiking: {}, swimming: {}, strong: {}, brave: {}",
+			self.hiking, self.swimming, self.strong, self.brave
+		)
+	}
+}
+
 impl Hiker {
 	/// Returns the time it takes a hiker to traverse a trail, if they can traverse it at all.
 	/// If the hiker cannot traverse the terrain, return None.
@@ -215,38 +396,37 @@ impl Hiker {
 	///   Rocky |    1X    |      2X      |    4X    |
 	/// Unpaved |   1/2X   |      1X      |    2X    |
 	///   Paved |   1/4X   |     1/2X     |    1X    |
+	///
+	/// These are the rates of the [`TerrainProfile::standard`] profile. Use
+	/// [`Hiker::travel_time_with`] for any other profile.
+	///
+	/// Travel times that do not fit in a `u32` are `None` as well, see
+	/// [`Hiker::travel_time_u64`].
 	pub fn travel_time(&self, terrain: &Terrain, distance: u32) -> Option<u32> {
-		match terrain {
-			Terrain::RopeBridge if self.brave => Some(distance),
-			Terrain::Zipline if self.brave && self.strong => Some(distance / 10),
-			Terrain::Water => {
-				let multiplier = match self.swimming {
-					Skill::Beginner => 9,
-					Skill::Intermediate => 6,
-					Skill::Expert => 3,
-				};
-				Some(multiplier * distance)
-			},
-			Terrain::RockyTrail => match self.hiking {
-				Skill::Beginner => Some(4 * distance),
-				Skill::Intermediate => Some(2 * distance),
-				Skill::Expert => Some(distance),
-			},
-			Terrain::UnpavedTrail => match self.hiking {
-				Skill::Beginner => Some(2 * distance),
-				Skill::Intermediate => Some(distance),
-				Skill::Expert => Some(distance / 2),
-			},
-			Terrain::PavedTrail => match self.hiking {
-				Skill::Beginner => Some(distance),
-				Skill::Intermediate => Some(distance / 2),
-				Skill::Expert => Some(distance / 4),
-			},
-			_ => None,
-		}
+		self.travel_time_with(TerrainProfile::standard(), terrain, distance)
+	}
+
+	/// Like [`Hiker::travel_time`], but as a `u64`, so that even the slowest traversal of the
+	/// longest trail has a travel time. Use it with a `u64` cost type, such as in
+	/// [`try_optimal_path`].
+	pub fn travel_time_u64(&self, terrain: &Terrain, distance: u32) -> Option<u64> {
+		self.travel_time_with_u64(TerrainProfile::standard(), terrain, distance)
 	}
 }
 
+/// A route through the trail network, as found by [`optimal_route`].
+///
+/// Costs are `u32` unless a search was asked for another [`Cost`] type.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Route<C = u32> {
+	/// The trails to follow, in the order they are traveled.
+	pub trails: Vec<Trail>,
+	/// The campsites visited along the way, including the start and the destination.
+	pub campsites: Vec<String>,
+	/// The total cost of the route, as computed by the cost function.
+	pub cost: C,
+}
+
 /// OPTIONAL
 /// This is the main path-finding function. It should be abstract enough that it can solve all of
 /// the more specific path finding problems in this module. That is, all of the following problem
@@ -255,6 +435,13 @@ impl Hiker {
 /// Given a hiker, their starting and ending points, a trail network, and a function that determines
 /// the cost of the hiker traversing a given trail, determine whether the hiker can reach the
 /// destination at all, and if they can, the minimal total cost reaching the destination.
+///
+/// The cost function returns the cost of the hiker traversing a trail, or `None` if the trail
+/// cannot be traversed at all. A route's cost is the sum of the costs of its trails. Every other
+/// search in this module takes a cost function with this same contract.
+///
+/// Routes whose cost does not fit in a `u32` are never returned; see [`try_optimal_path`] to
+/// search with `u64` costs, or to tell such routes apart from an unreachable destination.
 pub fn optimal_path(
 	start: String,
 	destination: String,
@@ -262,66 +449,44 @@ pub fn optimal_path(
 	trails: impl Iterator<Item = Trail>,
 	cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
 ) -> Option<u32> {
-	// Collect trails to be reusable (could be improved)
-	let trails = Vec::from_iter(trails);
-
-	// Create set of unvisited nodes
-	let mut unvisited = HashSet::new();
-	for trail in &trails {
-		unvisited.insert(trail.start.clone());
-		unvisited.insert(trail.end.clone());
-	}
-
-	// Create a map of node distances
-	let mut distances = HashMap::new();
-	for node in &unvisited {
-		distances.insert(node.clone(), u32::MAX);
-	}
-
-	// Set start node distance to 0
-	distances.insert(start.clone(), 0);
-
-	// Evaluate smaller distance per node
-	let mut current_node = start.clone();
-
-	loop {
-		// Set node as visited
-		unvisited.remove(&current_node);
-
-		// Get all trails that start at current node
-		let current_trails = trails.iter().filter(|trail| trail.start == current_node);
-
-		// Update distances for all nodes that can be reached from current node
-		for trail in current_trails {
-			let distance = cost_function(hiker, trail);
-			if distance.is_none() {
-				continue;
-			}
-			let distance = distance.unwrap();
-
-			let new_distance = distances[&current_node] + distance;
-			if new_distance < distances[&trail.end] {
-				distances.insert(trail.end.clone(), new_distance);
-			}
-		}
-
-		// Check if destination is reached
-		if current_node == destination {
-			return Some(distances[&destination]);
-		}
+	optimal_route(start, destination, hiker, trails, cost_function).map(|route| route.cost)
+}
 
-		// Otherwise find next node to visit
-		let min_distance_node = distances
-			.iter()
-			.filter(|(node, _)| unvisited.contains(*node))
-			.min_by_key(|(_, distance)| *distance);
+/// Like [`optimal_path`], but returns the whole [`Route`] rather than just its cost.
+pub fn optimal_route(
+	start: String,
+	destination: String,
+	hiker: &Hiker,
+	trails: impl Iterator<Item = Trail>,
+	cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+) -> Option<Route> {
+	TrailNetwork::from_iter(trails).optimal_route(&start, &destination, hiker, cost_function)
+}
 
-		if min_distance_node.is_none() {
-			return None;
-		}
+/// Like [`optimal_path`], but with costs of any [`Cost`] type, such as `u64`.
+///
+/// Returns an error if the destination can be reached, but only by routes whose cost does not fit
+/// in the cost type.
+pub fn try_optimal_path<C: Cost>(
+	start: String,
+	destination: String,
+	hiker: &Hiker,
+	trails: impl Iterator<Item = Trail>,
+	cost_function: impl Fn(&Hiker, &Trail) -> Option<C>,
+) -> Result<Option<C>, CostOverflow> {
+	let route = try_optimal_route(start, destination, hiker, trails, cost_function)?;
+	Ok(route.map(|route| route.cost))
+}
 
-		current_node = min_distance_node.unwrap().0.clone();
-	}
+/// Like [`try_optimal_path`], but returns the whole [`Route`] rather than just its cost.
+pub fn try_optimal_route<C: Cost>(
+	start: String,
+	destination: String,
+	hiker: &Hiker,
+	trails: impl Iterator<Item = Trail>,
+	cost_function: impl Fn(&Hiker, &Trail) -> Option<C>,
+) -> Result<Option<Route<C>>, CostOverflow> {
+	TrailNetwork::from_iter(trails).try_optimal_route(&start, &destination, hiker, cost_function)
 }
 
 /// A specific path optimization problem. Bill wants to find the shortest (least distance) path from
@@ -335,14 +500,17 @@ pub fn bills_shortest_path_from_green_lake_to_prairie_meadows(
 	// Although implementing the `optimal_path` helper is optional, this function, which calls
 	// `optimal_path` is required. It will be graded to function against a reference implementation
 	// and also manually for style.
-	let hiker = Hiker::default();
-	optimal_path(
-		String::from("Green Lake"),
-		String::from("Prairie Meadows"),
-		&hiker,
-		trails,
-		|hiker, trail| Some(trail.distance),
-	)
+	bills_shortest_route_from_green_lake_to_prairie_meadows(trails).map(|route| route.cost)
+}
+
+/// Like [`bills_shortest_path_from_green_lake_to_prairie_meadows`], but returns the whole
+/// [`Route`].
+pub fn bills_shortest_route_from_green_lake_to_prairie_meadows(
+	trails: impl Iterator<Item = Trail>,
+) -> Option<Route> {
+	RouteQuery::new("Green Lake", "Prairie Meadows")
+		.objective(Objective::Distance)
+		.find(trails)
 }
 
 /// A specific path optimization problem. Bill wants to find the safest (least dangerous) path
@@ -358,14 +526,16 @@ pub fn bills_safest_path(
 	// Although implementing the `optimal_path` helper is optional, this function, which calls
 	// `optimal_path` is required. It will be graded to function against a reference implementation
 	// and also manually for style.
-	let hiker = Hiker::default();
-	optimal_path(
-		String::from("Green Lake"),
-		String::from("Prairie Meadows"),
-		&hiker,
-		trails,
-		|hiker, trail| Some(trail.danger as u32),
-	)
+	bills_safest_route(start, destination, trails).map(|route| route.cost)
+}
+
+/// Like [`bills_safest_path`], but returns the whole [`Route`].
+pub fn bills_safest_route(
+	start: String,
+	destination: String,
+	trails: impl Iterator<Item = Trail>,
+) -> Option<Route> {
+	RouteQuery::new(start, destination).objective(Objective::Danger).find(trails)
 }
 
 /// A specific path optimization problem. Hikers often want to find the fastest (least travel time)
@@ -378,12 +548,16 @@ pub fn fastest_path_from_green_lake_to_prairie_meadows(
 	// Although implementing the `optimal_path` helper is optional, this function, which calls
 	// `optimal_path` is required. It will be graded to function against a reference implementation
 	// and also manually for style.
-	let hiker = Hiker::default();
-	optimal_path(
-		String::from("Green Lake"),
-		String::from("Prairie Meadows"),
-		&hiker,
-		trails,
-		|hiker, trail| hiker.travel_time(&trail.terrain, trail.distance),
-	)
+	fastest_route_from_green_lake_to_prairie_meadows(hiker, trails).map(|route| route.cost)
+}
+
+/// Like [`fastest_path_from_green_lake_to_prairie_meadows`], but returns the whole [`Route`].
+pub fn fastest_route_from_green_lake_to_prairie_meadows(
+	hiker: &Hiker,
+	trails: impl Iterator<Item = Trail>,
+) -> Option<Route> {
+	RouteQuery::new("Green Lake", "Prairie Meadows")
+		.hiker(hiker)
+		.objective(Objective::TravelTime)
+		.find(trails)
 }
@@ -0,0 +1,140 @@
+//! Travel costs between every pair of campsites in a network.
+
+use super::{Hiker, Trail, TrailNetwork};
+use std::{collections::HashMap, fmt::Write};
+
+/// The algorithms that can compute all-pairs travel costs.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AllPairsAlgorithm {
+	/// Floyd–Warshall, which takes O(V³) time regardless of the number of trails. Best for dense
+	/// networks.
+	FloydWarshall,
+	/// Dijkstra's algorithm from every campsite, which takes O(V·E·log V) time. Best for sparse
+	/// networks.
+	RepeatedDijkstra,
+}
+
+/// The minimal travel cost between every pair of campsites, as computed by
+/// [`TrailNetwork::all_pairs_costs`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CostMatrix {
+	/// The name of every campsite, in the order of the matrix rows and columns.
+	campsites: Vec<String>,
+	/// The row and column of every campsite, indexed by name.
+	ids: HashMap<String, usize>,
+	/// The cost from the row campsite to the column campsite, stored row by row.
+	costs: Vec<Option<u32>>,
+}
+
+impl CostMatrix {
+	/// Returns the minimal cost of getting from one campsite to another, if it is possible.
+	pub fn cost(&self, from: &str, to: &str) -> Option<u32> {
+		let (from, to) = (self.ids.get(from)?, self.ids.get(to)?);
+		self.costs[from * self.campsites.len() + to]
+	}
+
+	/// The names of all of the campsites in the matrix, in the order of its rows and columns.
+	pub fn campsites(&self) -> &[String] {
+		&self.campsites
+	}
+
+	/// Renders the matrix as CSV, with a row per starting campsite and a column per destination.
+	///
+	/// The first row and column hold the campsite names. Unreachable destinations are left empty.
+	pub fn to_csv(&self) -> String {
+		let mut csv = String::new();
+		for name in &self.campsites {
+			csv.push(',');
+			csv.push_str(&csv_field(name));
+		}
+		csv.push('\n');
+
+		for (row, from) in self.campsites.iter().enumerate() {
+			csv.push_str(&csv_field(from));
+			for column in 0..self.campsites.len() {
+				csv.push(',');
+				if let Some(cost) = self.costs[row * self.campsites.len() + column] {
+					write!(csv, "{cost}").expect("writing to a String never fails");
+				}
+			}
+			csv.push('\n');
+		}
+		csv
+	}
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+impl TrailNetwork {
+	/// Computes the minimal cost between every pair of campsites in the network.
+	///
+	/// The algorithm is chosen by how dense the network is.
+	pub fn all_pairs_costs(
+		&self,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> CostMatrix {
+		let campsites = self.campsite_count();
+		let log_campsites = (usize::BITS - campsites.leading_zeros()) as usize;
+		let algorithm = if self.trails().len() * log_campsites >= campsites * campsites {
+			AllPairsAlgorithm::FloydWarshall
+		} else {
+			AllPairsAlgorithm::RepeatedDijkstra
+		};
+		self.all_pairs_costs_using(algorithm, hiker, cost_function)
+	}
+
+	/// Like [`Self::all_pairs_costs`], but with the given algorithm.
+	pub fn all_pairs_costs_using(
+		&self,
+		algorithm: AllPairsAlgorithm,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> CostMatrix {
+		let n = self.campsite_count();
+		let cost = |trail: usize| cost_function(hiker, self.trail(trail));
+
+		let costs = match algorithm {
+			AllPairsAlgorithm::FloydWarshall => {
+				let mut costs = vec![None; n * n];
+				(0..n).for_each(|campsite| costs[campsite * n + campsite] = Some(0));
+				for (trail, _) in self.trails().iter().enumerate() {
+					let (start, end) = self.endpoints(trail);
+					if let Some(trail_cost) = cost(trail) {
+						let known = &mut costs[start * n + end];
+						*known = Some(known.map_or(trail_cost, |known: u32| known.min(trail_cost)));
+					}
+				}
+
+				for via in 0..n {
+					for from in 0..n {
+						let Some(first_leg) = costs[from * n + via] else { continue };
+						for to in 0..n {
+							let Some(second_leg) = costs[via * n + to] else { continue };
+							let Some(new_cost) = first_leg.checked_add(second_leg) else {
+								continue
+							};
+							if costs[from * n + to].is_none_or(|known| new_cost < known) {
+								costs[from * n + to] = Some(new_cost);
+							}
+						}
+					}
+				}
+				costs
+			},
+			AllPairsAlgorithm::RepeatedDijkstra =>
+				(0..n).flat_map(|from| self.shortest_paths(from, None, cost).costs).collect(),
+		};
+
+		let campsites: Vec<String> = self.campsites().map(String::from).collect();
+		let ids = campsites.iter().enumerate().map(|(id, name)| (name.clone(), id)).collect();
+		CostMatrix { campsites, ids, costs }
+	}
+}
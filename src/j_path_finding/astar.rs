@@ -0,0 +1,142 @@
+//! A* search, guided by the locations of campsites.
+//!
+//! Campsites may optionally be given [`Coordinates`]. A* uses a [`Heuristic`] to estimate the
+//! remaining cost from a campsite to the destination, so it can head towards the destination
+//! instead of searching in every direction. Wherever coordinates are missing, the estimate is
+//! zero, and the search behaves exactly like Dijkstra's algorithm.
+
+use super::{network::ShortestPaths, Hiker, Route, Trail, TrailNetwork};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// The location of a campsite on a flat map, in meters.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Coordinates {
+	pub x: f64,
+	pub y: f64,
+}
+
+impl Coordinates {
+	/// The straight-line distance between two locations, in meters.
+	pub fn distance_to(&self, other: &Coordinates) -> f64 {
+		(self.x - other.x).hypot(self.y - other.y)
+	}
+}
+
+/// An estimate of the cost of getting from one location to another.
+///
+/// For A* to find optimal routes, the heuristic must be admissible: it must never estimate more
+/// than the actual cost of the cheapest route between the two campsites.
+pub trait Heuristic {
+	fn estimate(&self, from: &Coordinates, to: &Coordinates) -> u32;
+}
+
+impl<F: Fn(&Coordinates, &Coordinates) -> u32> Heuristic for F {
+	fn estimate(&self, from: &Coordinates, to: &Coordinates) -> u32 {
+		self(from, to)
+	}
+}
+
+/// Estimates the cost as the straight-line distance, multiplied by the cheapest possible cost per
+/// meter.
+///
+/// A scale of 1 is admissible for distance, as long as no trail is shorter than the straight line
+/// between its ends. For travel time, use the fastest rate any trail may be traversed at, such as
+/// 1/10 for a network with ziplines.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StraightLine {
+	pub cost_per_meter: f64,
+}
+
+impl Heuristic for StraightLine {
+	fn estimate(&self, from: &Coordinates, to: &Coordinates) -> u32 {
+		// Rounding down keeps the estimate admissible
+		(from.distance_to(to) * self.cost_per_meter).floor() as u32
+	}
+}
+
+/// The result of an A* search.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AStarOutcome {
+	/// The cheapest route found, if the destination can be reached at all.
+	pub route: Option<Route>,
+	/// The number of campsites expanded by the search, a measure of how much work it did.
+	pub expanded: usize,
+}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination` using A* search.
+	///
+	/// As long as the heuristic is admissible, the route's cost is the same as the one found by
+	/// [`TrailNetwork::optimal_route`].
+	pub fn astar_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+		heuristic: &impl Heuristic,
+	) -> Option<Route> {
+		self.astar(start, destination, hiker, cost_function, heuristic).route
+	}
+
+	/// Like [`Self::astar_route`], but also reports how many campsites the search expanded.
+	pub fn astar(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+		heuristic: &impl Heuristic,
+	) -> AStarOutcome {
+		let (Some(start), Some(destination)) =
+			(self.campsite_id(start), self.campsite_id(destination))
+		else {
+			return AStarOutcome { route: None, expanded: 0 }
+		};
+
+		let target = self.coordinates_of(destination);
+		let estimate = |campsite: usize| match (self.coordinates_of(campsite), target) {
+			(Some(from), Some(to)) => heuristic.estimate(&from, &to),
+			_ => 0,
+		};
+
+		let mut costs = vec![None; self.campsite_count()];
+		let mut previous = vec![None; self.campsite_count()];
+		let mut queue = BinaryHeap::new();
+		let mut expanded = 0;
+
+		costs[start] = Some(0);
+		queue.push(Reverse((estimate(start), 0, start)));
+
+		while let Some(Reverse((_, current_cost, current))) = queue.pop() {
+			// A campsite may be expanded again if a cheaper way to it is found, since missing
+			// coordinates can make the heuristic inconsistent. Only outdated entries are skipped.
+			if costs[current].is_some_and(|known| current_cost > known) {
+				continue
+			}
+			expanded += 1;
+
+			if current == destination {
+				let paths = ShortestPaths { costs, previous, overflowed: false };
+				let trails = self.path_to(start, destination, &paths);
+				let route = trails.map(|trails| self.route_along(start, &trails, current_cost));
+				return AStarOutcome { route, expanded }
+			}
+
+			for &trail in self.outgoing(current) {
+				let Some(trail_cost) = cost_function(hiker, self.trail(trail)) else { continue };
+				let (_, end) = self.endpoints(trail);
+				let Some(new_cost) = current_cost.checked_add(trail_cost) else { continue };
+				if costs[end].is_none_or(|known| new_cost < known) {
+					costs[end] = Some(new_cost);
+					previous[end] = Some(trail);
+					// A saturated estimate still orders the queue correctly
+					let priority = new_cost.saturating_add(estimate(end));
+					queue.push(Reverse((priority, new_cost, end)));
+				}
+			}
+		}
+
+		AStarOutcome { route: None, expanded }
+	}
+}
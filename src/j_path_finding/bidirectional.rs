@@ -0,0 +1,137 @@
+//! Bidirectional Dijkstra, for point-to-point queries over large networks.
+//!
+//! One search runs forward from the start along the trails leaving each campsite, and another runs
+//! backward from the destination along the trails arriving at each campsite, since every trail is
+//! one-way. The two take turns, each settling its closest campsite, until no route through the
+//! campsites still unsettled could beat the cheapest route found where the searches meet. Each
+//! search only has to cover about half the distance, so far fewer campsites are settled overall.
+
+use super::{network::ShortestPaths, Hiker, Route, Trail, TrailNetwork};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// One direction of a bidirectional search.
+struct Frontier {
+	/// The minimal known cost between the origin of the search and each campsite.
+	costs: Vec<Option<u32>>,
+	/// The id of the trail each campsite was last reached by.
+	previous: Vec<Option<usize>>,
+	/// Whether the cost of each campsite is final.
+	settled: Vec<bool>,
+	queue: BinaryHeap<Reverse<(u32, usize)>>,
+}
+
+impl Frontier {
+	fn new(campsites: usize, origin: usize) -> Self {
+		let mut costs = vec![None; campsites];
+		costs[origin] = Some(0);
+		Self {
+			costs,
+			previous: vec![None; campsites],
+			settled: vec![false; campsites],
+			queue: BinaryHeap::from([Reverse((0, origin))]),
+		}
+	}
+
+	/// The cost of the closest campsite that is not settled yet, if there is one.
+	fn next_cost(&mut self) -> Option<u32> {
+		// Stale entries are dropped here, so the top of the queue is always a real candidate
+		while let Some(&Reverse((cost, campsite))) = self.queue.peek() {
+			if !self.settled[campsite] {
+				return Some(cost)
+			}
+			self.queue.pop();
+		}
+		None
+	}
+}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination`, searching from both ends at once.
+	///
+	/// The route's cost is always the same as the one found by [`TrailNetwork::optimal_route`],
+	/// though when there are several cheapest routes, the two may pick different ones.
+	pub fn bidirectional_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<Route> {
+		if start == destination {
+			return Some(Route { trails: Vec::new(), campsites: vec![start.to_string()], cost: 0 })
+		}
+
+		let start = self.campsite_id(start)?;
+		let destination = self.campsite_id(destination)?;
+		let cost = |trail: usize| cost_function(hiker, self.trail(trail));
+
+		let mut forward = Frontier::new(self.campsite_count(), start);
+		let mut backward = Frontier::new(self.campsite_count(), destination);
+		// The cheapest route found so far, and the campsite where its two halves meet
+		let mut best: Option<(u32, usize)> = None;
+
+		while let (Some(forward_next), Some(backward_next)) =
+			(forward.next_cost(), backward.next_cost())
+		{
+			// Any route not found yet costs at least as much as the two closest unsettled campsites
+			let closest = forward_next.saturating_add(backward_next);
+			if best.is_some_and(|(best_cost, _)| closest >= best_cost) {
+				break
+			}
+
+			let is_forward = forward_next <= backward_next;
+			let (this, other) =
+				if is_forward { (&mut forward, &backward) } else { (&mut backward, &forward) };
+			let Some(Reverse((current_cost, current))) = this.queue.pop() else { break };
+			this.settled[current] = true;
+
+			let trails = if is_forward { self.outgoing(current) } else { self.incoming(current) };
+			for &trail in trails {
+				let Some(trail_cost) = cost(trail) else { continue };
+				let (trail_start, trail_end) = self.endpoints(trail);
+				let next = if is_forward { trail_end } else { trail_start };
+				let Some(new_cost) = current_cost.checked_add(trail_cost) else { continue };
+				if this.costs[next].is_none_or(|known| new_cost < known) {
+					this.costs[next] = Some(new_cost);
+					this.previous[next] = Some(trail);
+					this.queue.push(Reverse((new_cost, next)));
+				}
+
+				let through = this.costs[next].zip(other.costs[next]);
+				if let Some(total) = through.and_then(|(here, there)| here.checked_add(there)) {
+					if best.is_none_or(|(best_cost, _)| total < best_cost) {
+						best = Some((total, next));
+					}
+				}
+			}
+		}
+
+		let (cost, meeting) = best?;
+
+		// The first half of the route is rebuilt as usual, the second by following the backward
+		// search's trails from the meeting campsite to the destination
+		let paths =
+			ShortestPaths { costs: forward.costs, previous: forward.previous, overflowed: false };
+		let mut trails = self.path_to(start, meeting, &paths)?;
+		let mut current = meeting;
+		while current != destination {
+			let trail = backward.previous[current]?;
+			trails.push(trail);
+			current = self.endpoints(trail).1;
+		}
+
+		Some(self.route_along(start, &trails, cost))
+	}
+
+	/// Like [`Self::bidirectional_route`], but only returns the cost of the route.
+	pub fn bidirectional_path(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<u32> {
+		self.bidirectional_route(start, destination, hiker, cost_function)
+			.map(|route| route.cost)
+	}
+}
@@ -0,0 +1,72 @@
+//! Resource-constrained route search.
+//!
+//! A cost function can only rule out a single trail at a time, by returning `None`. A [`Budget`]
+//! limits a resource, such as danger, across a whole route instead.
+
+use super::{CostFunction, Hiker, Route, Trail, TrailNetwork};
+
+/// A limit on how much of some resource a route may use.
+///
+/// ### Example:
+/// No trail with a danger above 150, and at most 400 danger in total:
+/// ```
+/// # use pba_entrance_exam::j_path_finding::Budget;
+/// let budget = Budget {
+///     resource: &|_, trail| Some(trail.danger as u32),
+///     max_per_trail: Some(150),
+///     max_total: Some(400),
+/// };
+/// ```
+#[derive(Clone, Copy)]
+pub struct Budget<'a> {
+	/// The amount of the resource used by the hiker traversing a trail. As with cost functions,
+	/// `None` means the trail cannot be traversed.
+	pub resource: CostFunction<'a>,
+	/// The most of the resource any single trail on the route may use, if limited.
+	pub max_per_trail: Option<u32>,
+	/// The most of the resource the whole route may use, if limited.
+	pub max_total: Option<u32>,
+}
+
+impl Budget<'_> {
+	/// Returns whether the given usage of the resource is within this budget.
+	fn allows(&self, trail_usage: u32, total_usage: u32) -> bool {
+		self.max_per_trail.is_none_or(|max| trail_usage <= max) &&
+			self.max_total.is_none_or(|max| total_usage <= max)
+	}
+}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination` that stays within every budget.
+	///
+	/// The route's cost is measured by the cost function alone, not by the budgeted resources.
+	pub fn constrained_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+		budgets: &[Budget],
+	) -> Option<Route> {
+		let start = self.campsite_id(start)?;
+		let destination = self.campsite_id(destination)?;
+
+		// The primary cost comes first, so the lexicographically smallest feasible route is the
+		// cheapest one. The resources follow, so routes that are more expensive but use less of a
+		// resource are kept around in case the cheaper ones run out of budget.
+		let mut cost_functions: Vec<CostFunction> = vec![&cost_function];
+		cost_functions.extend(budgets.iter().map(|budget| budget.resource));
+
+		let is_feasible = |trail_costs: &[u32], total_costs: &[u32]| {
+			budgets.iter().zip(trail_costs[1..].iter().zip(&total_costs[1..])).all(
+				|(budget, (&trail_usage, &total_usage))| budget.allows(trail_usage, total_usage),
+			)
+		};
+
+		let best = self
+			.label_search(start, destination, hiker, &cost_functions, is_feasible, true)
+			.into_iter()
+			.next()?;
+		Some(Route { trails: best.trails, campsites: best.campsites, cost: best.cost[0] })
+	}
+}
@@ -0,0 +1,357 @@
+//! Contraction hierarchies, for answering many queries on a fixed network almost instantly.
+//!
+//! Preprocessing contracts the campsites one by one, from least to most important. Contracting a
+//! campsite removes it from the network, adding a shortcut trail between two of its neighbours
+//! wherever the cheapest route between them went through it. A query then only has to search
+//! upwards, towards more important campsites, from both ends, which touches a tiny part of the
+//! network.
+//!
+//! A hierarchy is built for one hiker and cost function, and only answers queries for those. It
+//! can be saved with `Display` and loaded back with [`ContractionHierarchy::from_reader`], so the
+//! preprocessing only has to happen once per map. The format has one record per line:
+//!
+//! ```text
+//! # Every campsite, with its rank, in order of id
+//! campsite 1 Green Lake
+//! campsite 0 Prairie Meadow
+//! # Every trail and shortcut, as the ids of its ends and its cost
+//! edge 0 1 700
+//! ```
+
+use super::{loader::load_lines, Hiker, LoadError, ParseError, Trail, TrailNetwork};
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashMap},
+	fmt,
+	io::BufRead,
+	str::FromStr,
+};
+
+/// The most campsites a witness search settles before giving up and adding the shortcut anyway.
+/// Extra shortcuts never make queries wrong, only a little slower.
+const WITNESS_SEARCH_LIMIT: usize = 500;
+
+/// A preprocessed network that answers cost queries for a single hiker and cost function.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ContractionHierarchy {
+	/// The name of every campsite, indexed by campsite id.
+	campsites: Vec<String>,
+	/// The id of every campsite, indexed by name.
+	ids: HashMap<String, usize>,
+	/// The order each campsite was contracted in, indexed by campsite id.
+	ranks: Vec<usize>,
+	/// The trails and shortcuts leaving each campsite for a higher ranked one, with their costs.
+	upward: Vec<Vec<(usize, u32)>>,
+	/// The trails and shortcuts arriving at each campsite from a higher ranked one, with their
+	/// costs.
+	downward: Vec<Vec<(usize, u32)>>,
+}
+
+/// The trails between campsites that are not contracted yet, with the cheapest cost of each.
+struct Remaining {
+	outgoing: Vec<HashMap<usize, u32>>,
+	incoming: Vec<HashMap<usize, u32>>,
+}
+
+impl Remaining {
+	/// Adds a trail, unless there is already one at most as cheap between the same campsites.
+	fn add(&mut self, from: usize, to: usize, cost: u32) {
+		if from == to || self.outgoing[from].get(&to).is_some_and(|&known| known <= cost) {
+			return
+		}
+		self.outgoing[from].insert(to, cost);
+		self.incoming[to].insert(from, cost);
+	}
+
+	/// Returns the shortcuts needed to contract `campsite`, as `(from, to, cost)`.
+	fn shortcuts(&self, campsite: usize) -> Vec<(usize, usize, u32)> {
+		let mut shortcuts = Vec::new();
+		for (&from, &cost_in) in &self.incoming[campsite] {
+			let via: Vec<(usize, u32)> = self.outgoing[campsite]
+				.iter()
+				.filter(|(&to, _)| to != from)
+				// A route too costly to represent can never be answered, so it needs no shortcut
+				.filter_map(|(&to, &cost_out)| Some((to, cost_in.checked_add(cost_out)?)))
+				.collect();
+			let Some(limit) = via.iter().map(|&(_, cost)| cost).max() else { continue };
+
+			// Look for a witness: a route at most as cheap that avoids the campsite
+			let witnesses = self.costs_avoiding(from, campsite, limit);
+			shortcuts.extend(
+				via.into_iter()
+					.filter(|(to, cost)| witnesses.get(to).is_none_or(|witness| witness > cost))
+					.map(|(to, cost)| (from, to, cost)),
+			);
+		}
+		shortcuts.sort_unstable();
+		shortcuts
+	}
+
+	/// Runs a limited Dijkstra search from `start` that never goes through `avoid` or past
+	/// `limit`.
+	fn costs_avoiding(&self, start: usize, avoid: usize, limit: u32) -> HashMap<usize, u32> {
+		let mut costs: HashMap<usize, u32> = HashMap::from([(start, 0)]);
+		let mut queue = BinaryHeap::from([Reverse((0, start))]);
+		let mut settled = 0;
+
+		while let Some(Reverse((current_cost, current))) = queue.pop() {
+			if costs[&current] < current_cost {
+				continue
+			}
+			settled += 1;
+			if settled > WITNESS_SEARCH_LIMIT {
+				break
+			}
+
+			for (&next, &cost) in &self.outgoing[current] {
+				let Some(new_cost) = current_cost.checked_add(cost) else { continue };
+				if next == avoid || new_cost > limit {
+					continue
+				}
+				if costs.get(&next).is_none_or(|&known| new_cost < known) {
+					costs.insert(next, new_cost);
+					queue.push(Reverse((new_cost, next)));
+				}
+			}
+		}
+		costs
+	}
+}
+
+impl TrailNetwork {
+	/// Builds a contraction hierarchy for the given hiker and cost function.
+	///
+	/// Campsites are contracted in order of how many shortcuts contracting them would add, less the
+	/// trails it removes, so that the hierarchy stays small.
+	pub fn contraction_hierarchy(
+		&self,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> ContractionHierarchy {
+		let count = self.campsite_count();
+		let mut remaining = Remaining {
+			outgoing: vec![HashMap::new(); count],
+			incoming: vec![HashMap::new(); count],
+		};
+		for (id, trail) in self.trails().iter().enumerate() {
+			if let Some(cost) = cost_function(hiker, trail) {
+				let (start, end) = self.endpoints(id);
+				remaining.add(start, end, cost);
+			}
+		}
+
+		let mut hierarchy = ContractionHierarchy {
+			campsites: self.campsites().map(str::to_string).collect(),
+			ids: self.campsites().enumerate().map(|(id, name)| (name.to_string(), id)).collect(),
+			ranks: vec![0; count],
+			upward: vec![Vec::new(); count],
+			downward: vec![Vec::new(); count],
+		};
+
+		// Contracting next to campsites that are already contracted spreads the work evenly
+		let mut contracted_neighbours = vec![0; count];
+		let priority =
+			|remaining: &Remaining, neighbours: &[i64], campsite: usize, shortcuts: usize| {
+				let removed =
+					remaining.outgoing[campsite].len() + remaining.incoming[campsite].len();
+				shortcuts as i64 - removed as i64 + neighbours[campsite]
+			};
+
+		let mut queue: BinaryHeap<Reverse<(i64, usize)>> = (0..count)
+			.map(|campsite| {
+				let shortcuts = remaining.shortcuts(campsite).len();
+				Reverse((
+					priority(&remaining, &contracted_neighbours, campsite, shortcuts),
+					campsite,
+				))
+			})
+			.collect();
+		let mut rank = 0;
+
+		while let Some(Reverse((_, campsite))) = queue.pop() {
+			// Priorities go stale as neighbours are contracted, so check again before contracting
+			let shortcuts = remaining.shortcuts(campsite);
+			let current = priority(&remaining, &contracted_neighbours, campsite, shortcuts.len());
+			if queue.peek().is_some_and(|&Reverse((next, _))| current > next) {
+				queue.push(Reverse((current, campsite)));
+				continue
+			}
+
+			let mut upward: Vec<(usize, u32)> = remaining.outgoing[campsite].drain().collect();
+			let mut downward: Vec<(usize, u32)> = remaining.incoming[campsite].drain().collect();
+			upward.sort_unstable();
+			downward.sort_unstable();
+
+			for &(to, _) in &upward {
+				remaining.incoming[to].remove(&campsite);
+				contracted_neighbours[to] += 1;
+			}
+			for &(from, _) in &downward {
+				remaining.outgoing[from].remove(&campsite);
+				contracted_neighbours[from] += 1;
+			}
+			for (from, to, cost) in shortcuts {
+				remaining.add(from, to, cost);
+			}
+
+			hierarchy.ranks[campsite] = rank;
+			hierarchy.upward[campsite] = upward;
+			hierarchy.downward[campsite] = downward;
+			rank += 1;
+		}
+
+		hierarchy
+	}
+}
+
+impl ContractionHierarchy {
+	/// Returns the minimal cost of getting from one campsite to another, if it is possible.
+	///
+	/// This is always the same as the cost [`TrailNetwork::optimal_path`] finds for the hiker and
+	/// cost function the hierarchy was built for.
+	pub fn cost(&self, from: &str, to: &str) -> Option<u32> {
+		if from == to {
+			return Some(0)
+		}
+
+		let forward = Self::upward_costs(*self.ids.get(from)?, &self.upward);
+		let backward = Self::upward_costs(*self.ids.get(to)?, &self.downward);
+
+		// Every cheapest route climbs to its highest ranked campsite and then descends, so both
+		// searches reach that campsite
+		forward
+			.iter()
+			.filter_map(|(campsite, there)| Some(*there as u64 + *backward.get(campsite)? as u64))
+			.min()
+			.and_then(|cost| u32::try_from(cost).ok())
+	}
+
+	/// The names of all of the campsites in the hierarchy.
+	pub fn campsites(&self) -> &[String] {
+		&self.campsites
+	}
+
+	/// Runs Dijkstra's algorithm from `start` over the given edges, which only lead upwards.
+	fn upward_costs(start: usize, edges: &[Vec<(usize, u32)>]) -> HashMap<usize, u32> {
+		let mut costs: HashMap<usize, u32> = HashMap::from([(start, 0)]);
+		let mut queue = BinaryHeap::from([Reverse((0, start))]);
+
+		while let Some(Reverse((current_cost, current))) = queue.pop() {
+			if costs[&current] < current_cost {
+				continue
+			}
+			for &(next, cost) in &edges[current] {
+				let Some(new_cost) = current_cost.checked_add(cost) else { continue };
+				if costs.get(&next).is_none_or(|&known| new_cost < known) {
+					costs.insert(next, new_cost);
+					queue.push(Reverse((new_cost, next)));
+				}
+			}
+		}
+		costs
+	}
+
+	/// Adds an edge, from either the upward or the downward graph, depending on which end is
+	/// ranked higher.
+	fn add_edge(&mut self, from: usize, to: usize, cost: u32) {
+		if self.ranks[from] < self.ranks[to] {
+			self.upward[from].push((to, cost));
+		} else {
+			self.downward[to].push((from, cost));
+		}
+	}
+
+	/// Loads a hierarchy saved with `Display` from any buffered reader.
+	///
+	/// As for trail networks, every malformed line is reported together.
+	pub fn from_reader(reader: impl BufRead) -> Result<ContractionHierarchy, LoadError> {
+		let mut hierarchy = ContractionHierarchy::default();
+		load_lines(reader, |line| hierarchy.parse_line(line))?;
+		Ok(hierarchy)
+	}
+
+	/// Parses a single record and adds it to the hierarchy.
+	fn parse_line(&mut self, line: &str) -> Result<(), ParseError> {
+		let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+		let rest_offset = kind.len() + 1;
+
+		match kind {
+			"campsite" => {
+				let (rank, name) = rest
+					.split_once(' ')
+					.ok_or(ParseError::MissingDelimiter { offset: line.len(), delimiter: " " })?;
+				let rank = parse_number(rank, rest_offset)?;
+				if name.is_empty() {
+					return Err(ParseError::EmptyCampsite { offset: line.len() })
+				}
+				self.ids.insert(name.to_string(), self.campsites.len());
+				self.campsites.push(name.to_string());
+				self.ranks.push(rank);
+				self.upward.push(Vec::new());
+				self.downward.push(Vec::new());
+			},
+			"edge" => {
+				// Pair every field with its byte offset
+				let mut fields = rest.split(' ').scan(rest_offset, |offset, field| {
+					let start = *offset;
+					*offset += field.len() + 1;
+					Some((start, field))
+				});
+				let mut next_id = || {
+					let (offset, field) = fields.next().ok_or(ParseError::MissingDelimiter {
+						offset: line.len(),
+						delimiter: " ",
+					})?;
+					let id: usize = parse_number(field, offset)?;
+					if id >= self.campsites.len() {
+						return Err(ParseError::InvalidNumber { offset, value: field.to_string() })
+					}
+					Ok(id)
+				};
+				let (from, to) = (next_id()?, next_id()?);
+				let (offset, cost) = fields
+					.next()
+					.ok_or(ParseError::MissingDelimiter { offset: line.len(), delimiter: " " })?;
+				let cost = parse_number(cost, offset)?;
+				self.add_edge(from, to, cost);
+			},
+			_ => return Err(ParseError::UnknownRecord { offset: 0, kind: kind.to_string() }),
+		}
+		Ok(())
+	}
+}
+
+/// Parses a number in a hierarchy record that starts at the given offset.
+fn parse_number<T: FromStr>(value: &str, offset: usize) -> Result<T, ParseError> {
+	value
+		.parse()
+		.map_err(|_| ParseError::InvalidNumber { offset, value: value.to_string() })
+}
+
+impl fmt::Display for ContractionHierarchy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (name, rank) in self.campsites.iter().zip(&self.ranks) {
+			writeln!(f, "campsite {rank} {name}")?;
+		}
+		for (from, edges) in self.upward.iter().enumerate() {
+			for (to, cost) in edges {
+				writeln!(f, "edge {from} {to} {cost}")?;
+			}
+		}
+		for (to, edges) in self.downward.iter().enumerate() {
+			for (from, cost) in edges {
+				writeln!(f, "edge {from} {to} {cost}")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for ContractionHierarchy {
+	type Err = LoadError;
+
+	/// Loads a hierarchy saved with `Display`.
+	fn from_str(document: &str) -> Result<Self, Self::Err> {
+		ContractionHierarchy::from_reader(document.as_bytes())
+	}
+}
@@ -0,0 +1,50 @@
+//! The numeric types route costs can be computed in.
+//!
+//! Costs are added up with checked arithmetic, so a route whose cost does not fit in the cost type
+//! is never returned with a wrapped-around cost. Searches simply skip such routes, and the
+//! `try_` searches report when the destination could only have been reached by one of them. Long
+//! and costly routes can be searched with `u64` costs instead of `u32`.
+
+use super::{Hiker, Trail};
+use std::fmt;
+
+/// A cost function, as described on [`super::optimal_path`].
+pub type CostFunction<'a> = &'a dyn Fn(&Hiker, &Trail) -> Option<u32>;
+
+/// A numeric type route costs can be computed in, such as `u32` or `u64`.
+pub trait Cost: Copy + Ord + Default + fmt::Debug {
+	/// Adds two costs, returning `None` if the result cannot be represented.
+	fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+impl Cost for u32 {
+	fn checked_add(self, other: Self) -> Option<Self> {
+		u32::checked_add(self, other)
+	}
+}
+
+impl Cost for u64 {
+	fn checked_add(self, other: Self) -> Option<Self> {
+		u64::checked_add(self, other)
+	}
+}
+
+/// The error returned when the destination can be reached, but every route to it costs more than
+/// the cost type can represent.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CostOverflow {
+	pub start: String,
+	pub destination: String,
+}
+
+impl fmt::Display for CostOverflow {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"every route from `{}` to `{}` costs more than the cost type can represent",
+			self.start, self.destination
+		)
+	}
+}
+
+impl std::error::Error for CostOverflow {}
@@ -0,0 +1,136 @@
+//! The errors that can occur while parsing or writing the text encodings of this module's types.
+
+use std::fmt;
+
+/// The reasons parsing a [`super::Trail`], [`super::Terrain`], [`super::Skill`],
+/// [`super::Hiker`], or a line of a [`super::TerrainProfile`] or a
+/// [`super::ContractionHierarchy`] may fail.
+///
+/// Every variant carries the byte offset into the parsed string where the problem was found.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+	/// A delimiter of the format, such as `=>` or `(`, is missing.
+	MissingDelimiter { offset: usize, delimiter: &'static str },
+	/// A campsite name is empty.
+	EmptyCampsite { offset: usize },
+	/// A distance is not a number between 0 and `u32::MAX`.
+	InvalidDistance { offset: usize, value: String },
+	/// A terrain name is not one of the known terrains.
+	UnknownTerrain { offset: usize, name: String },
+	/// A danger rating is not a number.
+	InvalidDanger { offset: usize, value: String },
+	/// A danger rating is a number over 255.
+	DangerOutOfRange { offset: usize, value: String },
+	/// A skill name is not one of the known skills.
+	UnknownSkill { offset: usize, name: String },
+	/// A value that should be `true` or `false` is neither.
+	InvalidBool { offset: usize, value: String },
+	/// A hiker key is missing, because the string ended early.
+	MissingKey { offset: usize, expected: &'static str },
+	/// A hiker key is not the one expected at this position, for example because the keys are out
+	/// of order.
+	UnexpectedKey { offset: usize, expected: &'static str, found: String },
+	/// A terrain profile rate is not a whole number or a fraction with a non-zero denominator.
+	InvalidRate { offset: usize, value: String },
+	/// A terrain profile line has a number of rates other than one or three.
+	WrongRateCount { offset: usize, count: usize },
+	/// A contraction hierarchy line does not start with a known kind of record.
+	UnknownRecord { offset: usize, kind: String },
+	/// A contraction hierarchy number is not a valid number, or refers to a campsite that has not
+	/// been listed yet.
+	InvalidNumber { offset: usize, value: String },
+}
+
+impl ParseError {
+	/// The byte offset into the parsed string where the problem was found.
+	pub fn offset(&self) -> usize {
+		*self.offset_ref()
+	}
+
+	/// Moves the error's offset forward, for when the parsed string was part of a larger one.
+	pub(crate) fn offset_by(mut self, by: usize) -> Self {
+		*self.offset_mut() += by;
+		self
+	}
+
+	fn offset_ref(&self) -> &usize {
+		match self {
+			ParseError::MissingDelimiter { offset, .. } |
+			ParseError::EmptyCampsite { offset } |
+			ParseError::InvalidDistance { offset, .. } |
+			ParseError::UnknownTerrain { offset, .. } |
+			ParseError::InvalidDanger { offset, .. } |
+			ParseError::DangerOutOfRange { offset, .. } |
+			ParseError::UnknownSkill { offset, .. } |
+			ParseError::InvalidBool { offset, .. } |
+			ParseError::MissingKey { offset, .. } |
+			ParseError::UnexpectedKey { offset, .. } |
+			ParseError::InvalidRate { offset, .. } |
+			ParseError::WrongRateCount { offset, .. } |
+			ParseError::UnknownRecord { offset, .. } |
+			ParseError::InvalidNumber { offset, .. } => offset,
+		}
+	}
+
+	fn offset_mut(&mut self) -> &mut usize {
+		match self {
+			ParseError::MissingDelimiter { offset, .. } |
+			ParseError::EmptyCampsite { offset } |
+			ParseError::InvalidDistance { offset, .. } |
+			ParseError::UnknownTerrain { offset, .. } |
+			ParseError::InvalidDanger { offset, .. } |
+			ParseError::DangerOutOfRange { offset, .. } |
+			ParseError::UnknownSkill { offset, .. } |
+			ParseError::InvalidBool { offset, .. } |
+			ParseError::MissingKey { offset, .. } |
+			ParseError::UnexpectedKey { offset, .. } |
+			ParseError::InvalidRate { offset, .. } |
+			ParseError::WrongRateCount { offset, .. } |
+			ParseError::UnknownRecord { offset, .. } |
+			ParseError::InvalidNumber { offset, .. } => offset,
+		}
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ParseError::MissingDelimiter { delimiter, .. } => write!(f, "missing `{delimiter}`"),
+			ParseError::EmptyCampsite { .. } => write!(f, "campsite name is empty"),
+			ParseError::InvalidDistance { value, .. } => write!(f, "invalid distance `{value}`"),
+			ParseError::UnknownTerrain { name, .. } => write!(f, "unknown terrain `{name}`"),
+			ParseError::InvalidDanger { value, .. } => write!(f, "invalid danger `{value}`"),
+			ParseError::DangerOutOfRange { value, .. } =>
+				write!(f, "danger `{value}` is over the maximum of 255"),
+			ParseError::UnknownSkill { name, .. } => write!(f, "unknown skill `{name}`"),
+			ParseError::InvalidBool { value, .. } =>
+				write!(f, "expected `true` or `false`, found `{value}`"),
+			ParseError::MissingKey { expected, .. } => write!(f, "missing key `{expected}`"),
+			ParseError::UnexpectedKey { expected, found, .. } =>
+				write!(f, "expected key `{expected}`, found `{found}`"),
+			ParseError::InvalidRate { value, .. } => write!(f, "invalid rate `{value}`"),
+			ParseError::WrongRateCount { count, .. } =>
+				write!(f, "expected 1 or 3 rates, found {count}"),
+			ParseError::UnknownRecord { kind, .. } => write!(f, "unknown record `{kind}`"),
+			ParseError::InvalidNumber { value, .. } => write!(f, "invalid number `{value}`"),
+		}?;
+		write!(f, " at byte {}", self.offset())
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/// The error returned when a value cannot be written in its text encoding, because one of its
+/// campsite names would not parse back unchanged. See [`super::Trail::is_encodable_campsite`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnencodableCampsite {
+	pub name: String,
+}
+
+impl fmt::Display for UnencodableCampsite {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "campsite name {:?} would not parse back unchanged", self.name)
+	}
+}
+
+impl std::error::Error for UnencodableCampsite {}
@@ -0,0 +1,165 @@
+//! Explanations of the routes the planner picks, for hikers who want to know why they were sent
+//! one way and not another.
+
+use super::{validation::described, Hiker, Route, TerrainProfile, Trail, TrailNetwork};
+use std::fmt;
+
+/// One trail of an explained route.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrailBreakdown {
+	/// The trail, with its terrain, distance and danger.
+	pub trail: Trail,
+	/// How long the hiker takes to traverse the trail.
+	pub travel_time: u64,
+	/// The cost of the trail, as computed by the cost function.
+	pub cost: u32,
+}
+
+/// Why a hiker cannot traverse a trail.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Rejection {
+	/// The terrain is only for brave hikers.
+	NotBrave,
+	/// The terrain is only for strong hikers.
+	NotStrong,
+	/// The terrain profile has no rate for the terrain.
+	NoRate,
+}
+
+impl fmt::Display for Rejection {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Rejection::NotBrave => "not brave",
+			Rejection::NotStrong => "not strong",
+			Rejection::NoRate => "no rate for this terrain",
+		})
+	}
+}
+
+/// A trail leaving the start that the hiker cannot traverse, and every reason why.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RejectedTrail {
+	pub trail: Trail,
+	pub reasons: Vec<Rejection>,
+}
+
+/// The route picked from one campsite to another, trail by trail, along with the trails leaving
+/// the start that were not taken for reasons other than their cost.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RouteExplanation {
+	/// The cheapest route the hiker can traverse, if the destination can be reached at all.
+	pub route: Option<Route>,
+	/// Every trail on the route, in the order they are traveled.
+	pub trails: Vec<TrailBreakdown>,
+	/// The trails leaving the start that the hiker cannot traverse.
+	pub infeasible: Vec<RejectedTrail>,
+	/// The trails leaving the start that the hiker could traverse, but the cost function ruled
+	/// out.
+	pub excluded: Vec<Trail>,
+}
+
+impl fmt::Display for RouteExplanation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.route {
+			Some(route) => writeln!(f, "route costs {}:", route.cost)?,
+			None => writeln!(f, "no route")?,
+		}
+		for TrailBreakdown { trail, travel_time, cost } in &self.trails {
+			writeln!(f, "  {}, travel time {travel_time}, cost {cost}", described(trail))?;
+		}
+		for RejectedTrail { trail, reasons } in &self.infeasible {
+			let reasons: Vec<String> = reasons.iter().map(Rejection::to_string).collect();
+			writeln!(f, "cannot take {}: {}", described(trail), reasons.join(", "))?;
+		}
+		for trail in &self.excluded {
+			writeln!(f, "excluded by the cost function: {}", described(trail))?;
+		}
+		Ok(())
+	}
+}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination` that the hiker can traverse, and
+	/// explains it, with the travel times of the standard [`TerrainProfile`].
+	///
+	/// Returns `None` if the start is not part of the network. See
+	/// [`TrailNetwork::explain_route_with`].
+	pub fn explain_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<RouteExplanation> {
+		self.explain_route_with(
+			TerrainProfile::standard(),
+			start,
+			destination,
+			hiker,
+			cost_function,
+		)
+	}
+
+	/// Like [`TrailNetwork::explain_route`], but with the travel times of the given profile.
+	///
+	/// Trails the hiker cannot traverse under the profile are never taken, whatever the cost
+	/// function says. Those leaving the start are listed with every reason why, separately from
+	/// the ones the cost function ruled out.
+	pub fn explain_route_with(
+		&self,
+		profile: &TerrainProfile,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<RouteExplanation> {
+		let start_id = self.campsite_id(start)?;
+		let travel_time =
+			|trail: &Trail| hiker.travel_time_with_u64(profile, &trail.terrain, trail.distance);
+		let route = self.optimal_route(start, destination, hiker, |hiker, trail| {
+			travel_time(trail)?;
+			cost_function(hiker, trail)
+		});
+
+		let trails = route
+			.iter()
+			.flat_map(|route| &route.trails)
+			.filter_map(|trail| {
+				Some(TrailBreakdown {
+					trail: trail.clone(),
+					travel_time: travel_time(trail)?,
+					cost: cost_function(hiker, trail)?,
+				})
+			})
+			.collect();
+
+		let mut infeasible = Vec::new();
+		let mut excluded = Vec::new();
+		for trail in self.outgoing(start_id).iter().map(|&trail| self.trail(trail)) {
+			let reasons = rejections(profile, hiker, trail);
+			if !reasons.is_empty() {
+				infeasible.push(RejectedTrail { trail: trail.clone(), reasons });
+			} else if cost_function(hiker, trail).is_none() {
+				excluded.push(trail.clone());
+			}
+		}
+
+		Some(RouteExplanation { route, trails, infeasible, excluded })
+	}
+}
+
+/// Every reason the hiker cannot traverse a trail, if any.
+fn rejections(profile: &TerrainProfile, hiker: &Hiker, trail: &Trail) -> Vec<Rejection> {
+	let rules = trail.terrain.rules();
+	let mut reasons = Vec::new();
+	if rules.requires_brave && !hiker.brave {
+		reasons.push(Rejection::NotBrave);
+	}
+	if rules.requires_strong && !hiker.strong {
+		reasons.push(Rejection::NotStrong);
+	}
+	if profile.rate(&trail.terrain, rules.skill(hiker)).is_none() {
+		reasons.push(Rejection::NoRate);
+	}
+	reasons
+}
@@ -0,0 +1,142 @@
+//! Exporting trail networks and routes for visualization, as Graphviz DOT or as JSON.
+//!
+//! Both formats list campsites and trails in the order they were added to the network, so the
+//! output is stable. A route can be passed along to highlight the trails it follows.
+
+use super::{Route, Terrain, Trail, TrailNetwork};
+use std::fmt::Write;
+
+/// The Graphviz line style for each terrain.
+fn dot_style(terrain: &Terrain) -> &'static str {
+	match terrain {
+		Terrain::PavedTrail => "solid",
+		Terrain::UnpavedTrail => "dashed",
+		Terrain::RockyTrail => "dotted",
+		Terrain::Water => "tapered",
+		Terrain::Zipline => "bold",
+		Terrain::RopeBridge => "dashed,bold",
+		Terrain::Custom(_) => "solid",
+	}
+}
+
+/// The colour of a trail with the given danger, from green for safe trails to red for the most
+/// dangerous ones.
+fn dot_colour(danger: u8) -> String {
+	format!("#{:02x}{:02x}00", danger, 255 - danger)
+}
+
+/// Quotes a DOT identifier, escaping quotes and backslashes.
+fn dot_string(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quotes a JSON string, escaping quotes, backslashes and control characters.
+fn json_string(s: &str) -> String {
+	let mut quoted = String::from("\"");
+	for c in s.chars() {
+		match c {
+			'"' => quoted.push_str("\\\""),
+			'\\' => quoted.push_str("\\\\"),
+			'\n' => quoted.push_str("\\n"),
+			'\r' => quoted.push_str("\\r"),
+			'\t' => quoted.push_str("\\t"),
+			c if c.is_control() =>
+				write!(quoted, "\\u{:04x}", c as u32).expect("writing to a String never fails"),
+			c => quoted.push(c),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+
+/// Formats a coordinate as a JSON number. JSON has no representation for NaN or infinities.
+fn json_number(value: f64) -> String {
+	if value.is_finite() {
+		value.to_string()
+	} else {
+		"null".to_string()
+	}
+}
+
+impl TrailNetwork {
+	/// Returns, for every trail, whether it is on the given route.
+	fn on_route(&self, route: Option<&Route>) -> Vec<bool> {
+		self.trails()
+			.iter()
+			.map(|trail| route.is_some_and(|route| route.trails.contains(trail)))
+			.collect()
+	}
+
+	/// Renders the network as a Graphviz DOT digraph.
+	///
+	/// Each trail's line style shows its terrain, and its colour its danger, from green to red. The
+	/// trails and campsites of the given route, if any, are drawn thicker and filled in.
+	pub fn to_dot(&self, route: Option<&Route>) -> String {
+		let on_route = self.on_route(route);
+		let mut dot = String::from("digraph trails {\n");
+
+		for campsite in self.campsites() {
+			let visited = route.is_some_and(|route| route.campsites.iter().any(|c| c == campsite));
+			let attributes = if visited { " [style=filled, fillcolor=\"#ffd700\"]" } else { "" };
+			writeln!(dot, "\t{}{attributes};", dot_string(campsite))
+				.expect("writing to a String never fails");
+		}
+
+		for (trail, on_route) in self.trails().iter().zip(on_route) {
+			let Trail { start, end, distance, terrain, danger } = trail;
+			writeln!(
+				dot,
+				"\t{} -> {} [label={}, style={}, color={}{}];",
+				dot_string(start),
+				dot_string(end),
+				dot_string(&format!("{distance} ({terrain}) [{danger}]")),
+				dot_string(dot_style(terrain)),
+				dot_string(&dot_colour(*danger)),
+				if on_route { ", penwidth=3" } else { "" },
+			)
+			.expect("writing to a String never fails");
+		}
+
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// Renders the network as JSON, with a list of nodes and a list of edges.
+	///
+	/// Every node has an `id`, which is the campsite name, and `x` and `y` if its coordinates are
+	/// known. Every edge has `from`, `to`, `distance`, `terrain`, `danger` and `on_route`, which
+	/// is whether the trail is on the given route.
+	pub fn to_json(&self, route: Option<&Route>) -> String {
+		let nodes: Vec<String> = self
+			.campsites()
+			.map(|campsite| match self.coordinates(campsite) {
+				Some(at) => format!(
+					"{{\"id\":{},\"x\":{},\"y\":{}}}",
+					json_string(campsite),
+					json_number(at.x),
+					json_number(at.y)
+				),
+				None => format!("{{\"id\":{}}}", json_string(campsite)),
+			})
+			.collect();
+
+		let edges: Vec<String> = self
+			.trails()
+			.iter()
+			.zip(self.on_route(route))
+			.map(|(trail, on_route)| {
+				format!(
+					"{{\"from\":{},\"to\":{},\"distance\":{},\"terrain\":{},\"danger\":{},\
+					 \"on_route\":{on_route}}}",
+					json_string(&trail.start),
+					json_string(&trail.end),
+					trail.distance,
+					json_string(&trail.terrain.to_string()),
+					trail.danger,
+				)
+			})
+			.collect();
+
+		format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+	}
+}
@@ -0,0 +1,224 @@
+//! Multi-stop itineraries, visiting several waypoints on the way to a destination.
+
+use super::{Hiker, Route, Trail, TrailNetwork};
+
+/// The largest number of unordered waypoints for which the optimal visiting order is found exactly.
+/// Above this, the order is found heuristically.
+pub const EXACT_WAYPOINT_LIMIT: usize = 12;
+
+/// Whether waypoints must be visited in the order they are given.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WaypointOrder {
+	/// The waypoints are visited in the order given.
+	Fixed,
+	/// The waypoints may be visited in whatever order is cheapest.
+	Any,
+}
+
+/// A trip from a start to a destination through a number of waypoints, as planned by
+/// [`TrailNetwork::plan_itinerary`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Itinerary {
+	/// The whole trip, stitched together from its legs.
+	pub route: Route,
+	/// The campsites the trip stops at, in order: the start, the waypoints, then the destination.
+	pub stops: Vec<String>,
+	/// The cost of each leg of the trip, between consecutive stops.
+	pub leg_costs: Vec<u32>,
+}
+
+impl TrailNetwork {
+	/// Plans the cheapest trip from `start` to `destination` that visits every waypoint.
+	///
+	/// With [`WaypointOrder::Any`], the visiting order is optimal for up to
+	/// [`EXACT_WAYPOINT_LIMIT`] waypoints. Above that it is good but not necessarily optimal,
+	/// though never worse than visiting the waypoints in the order given.
+	pub fn plan_itinerary(
+		&self,
+		start: &str,
+		waypoints: &[&str],
+		destination: &str,
+		order: WaypointOrder,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<Itinerary> {
+		let start = self.campsite_id(start)?;
+		let destination = self.campsite_id(destination)?;
+		let waypoints: Vec<usize> =
+			waypoints.iter().map(|name| self.campsite_id(name)).collect::<Option<_>>()?;
+
+		// Stop 0 is the start and stops 1..=n are the waypoints. One search from each of them
+		// gives the cost of every possible leg.
+		let sources: Vec<usize> = std::iter::once(start).chain(waypoints.iter().copied()).collect();
+		let searches: Vec<_> = sources
+			.iter()
+			.map(|&source| {
+				self.shortest_paths(source, None, |trail| cost_function(hiker, self.trail(trail)))
+			})
+			.collect();
+		let leg_cost = |from: usize, to: usize| searches[from].costs[to];
+
+		let visiting_order: Vec<usize> = match order {
+			WaypointOrder::Fixed => (1..sources.len()).collect(),
+			WaypointOrder::Any if waypoints.len() <= EXACT_WAYPOINT_LIMIT =>
+				exact_order(&sources, destination, leg_cost)?,
+			WaypointOrder::Any => heuristic_order(&sources, destination, leg_cost)?,
+		};
+
+		// Stitch the legs between consecutive stops together
+		let mut route: Route = Route {
+			trails: Vec::new(),
+			campsites: vec![self.campsite_name(start).to_string()],
+			cost: 0,
+		};
+		let mut leg_costs = Vec::new();
+		let mut from = 0;
+		for to in visiting_order.iter().map(Some).chain([None]) {
+			let to_campsite = to.map_or(destination, |&stop| sources[stop]);
+			let leg = self.route_to(sources[from], to_campsite, &searches[from])?;
+			route.trails.extend(leg.trails);
+			route.campsites.extend(leg.campsites.into_iter().skip(1));
+			route.cost = route.cost.checked_add(leg.cost)?;
+			leg_costs.push(leg.cost);
+			from = to.copied().unwrap_or(from);
+		}
+
+		let stops = std::iter::once(start)
+			.chain(visiting_order.iter().map(|&stop| sources[stop]))
+			.chain([destination])
+			.map(|campsite| self.campsite_name(campsite).to_string())
+			.collect();
+		Some(Itinerary { route, stops, leg_costs })
+	}
+}
+
+/// The total cost of visiting the given stops in order, between the start and the destination.
+fn tour_cost(
+	order: &[usize],
+	sources: &[usize],
+	destination: usize,
+	leg_cost: impl Fn(usize, usize) -> Option<u32>,
+) -> Option<u32> {
+	let mut cost: u32 = 0;
+	let mut from = 0;
+	for &stop in order {
+		cost = cost.checked_add(leg_cost(from, sources[stop])?)?;
+		from = stop;
+	}
+	cost.checked_add(leg_cost(from, destination)?)
+}
+
+/// Finds the cheapest order to visit the waypoints in, using the Held–Karp algorithm.
+///
+/// Stops are numbered as in [`TrailNetwork::plan_itinerary`], and `leg_cost` gives the cost from a
+/// stop to a campsite. Returns the waypoint stops in visiting order.
+fn exact_order(
+	sources: &[usize],
+	destination: usize,
+	leg_cost: impl Fn(usize, usize) -> Option<u32>,
+) -> Option<Vec<usize>> {
+	let waypoints = sources.len() - 1;
+	let all = (1 << waypoints) - 1;
+
+	// The cheapest way to visit the set of waypoints in `mask`, ending at waypoint stop `last + 1`
+	let mut best: Vec<Vec<Option<u32>>> = vec![vec![None; waypoints]; 1 << waypoints];
+	let mut previous: Vec<Vec<Option<usize>>> = vec![vec![None; waypoints]; 1 << waypoints];
+	for first in 0..waypoints {
+		best[1 << first][first] = leg_cost(0, sources[first + 1]);
+	}
+
+	for mask in 1..=all {
+		for last in (0..waypoints).filter(|last| mask & (1 << last) != 0) {
+			let Some(cost) = best[mask][last] else { continue };
+			for next in (0..waypoints).filter(|next| mask & (1 << next) == 0) {
+				let Some(leg) = leg_cost(last + 1, sources[next + 1]) else { continue };
+				let Some(new_cost) = cost.checked_add(leg) else { continue };
+				let next_mask = mask | (1 << next);
+				if best[next_mask][next].is_none_or(|known| new_cost < known) {
+					best[next_mask][next] = Some(new_cost);
+					previous[next_mask][next] = Some(last);
+				}
+			}
+		}
+	}
+
+	if waypoints == 0 {
+		return leg_cost(0, destination).map(|_| Vec::new())
+	}
+
+	let (last, _) = (0..waypoints)
+		.filter_map(|last| {
+			Some((last, best[all][last]?.checked_add(leg_cost(last + 1, destination)?)?))
+		})
+		.min_by_key(|&(_, cost)| cost)?;
+
+	let mut order = Vec::new();
+	let (mut mask, mut current) = (all, Some(last));
+	while let Some(waypoint) = current {
+		order.push(waypoint + 1);
+		current = previous[mask][waypoint];
+		mask &= !(1 << waypoint);
+	}
+	order.reverse();
+	Some(order)
+}
+
+/// Finds a good order to visit the waypoints in. The better of the order they were given in and
+/// the order found by always visiting the nearest waypoint next is improved with 2-opt moves until
+/// none help, so the result is never worse than the given order.
+fn heuristic_order(
+	sources: &[usize],
+	destination: usize,
+	leg_cost: impl Fn(usize, usize) -> Option<u32>,
+) -> Option<Vec<usize>> {
+	let given: Vec<usize> = (1..sources.len()).collect();
+	let nearest_first = nearest_first_order(sources, &leg_cost);
+
+	let (mut order, mut best_cost) = [Some(given), nearest_first]
+		.into_iter()
+		.flatten()
+		.filter_map(|order| {
+			let cost = tour_cost(&order, sources, destination, &leg_cost)?;
+			Some((order, cost))
+		})
+		.min_by_key(|(_, cost)| *cost)?;
+
+	let mut improved = true;
+	while improved {
+		improved = false;
+		for i in 0..order.len() {
+			for j in i + 1..order.len() {
+				order[i..=j].reverse();
+				match tour_cost(&order, sources, destination, &leg_cost) {
+					Some(cost) if cost < best_cost => {
+						best_cost = cost;
+						improved = true;
+					},
+					_ => order[i..=j].reverse(),
+				}
+			}
+		}
+	}
+	Some(order)
+}
+
+/// Orders the waypoints by always visiting the nearest unvisited one next, if that never gets
+/// stuck.
+fn nearest_first_order(
+	sources: &[usize],
+	leg_cost: impl Fn(usize, usize) -> Option<u32>,
+) -> Option<Vec<usize>> {
+	let mut unvisited: Vec<usize> = (1..sources.len()).collect();
+	let mut order = Vec::new();
+	let mut from = 0;
+	while !unvisited.is_empty() {
+		let (index, _) = unvisited
+			.iter()
+			.enumerate()
+			.filter_map(|(index, &stop)| Some((index, leg_cost(from, sources[stop])?)))
+			.min_by_key(|&(_, cost)| cost)?;
+		from = unvisited.swap_remove(index);
+		order.push(from);
+	}
+	Some(order)
+}
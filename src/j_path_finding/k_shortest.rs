@@ -0,0 +1,96 @@
+//! Alternative routes, using Yen's algorithm for the k shortest loopless paths.
+
+use super::{Hiker, Route, Trail, TrailNetwork};
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashSet},
+};
+
+impl TrailNetwork {
+	/// Finds up to `k` distinct loopless routes from `start` to `destination`, cheapest first.
+	///
+	/// Routes are distinct when they use different trails, so two parallel trails between the same
+	/// campsites give two different routes.
+	pub fn k_shortest_routes(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		k: usize,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Vec<Route> {
+		let (Some(start), Some(destination)) =
+			(self.campsite_id(start), self.campsite_id(destination))
+		else {
+			return Vec::new()
+		};
+		if k == 0 {
+			return Vec::new()
+		}
+		let cost = |trail: usize| cost_function(hiker, self.trail(trail));
+		let path_cost = |trails: &[usize]| {
+			trails.iter().try_fold(0u32, |total, &trail| total.checked_add(cost(trail)?))
+		};
+
+		let paths = self.shortest_paths(start, Some(destination), cost);
+		let Some(shortest) = self.path_to(start, destination, &paths) else { return Vec::new() };
+
+		let Some(shortest_cost) = paths.costs[destination] else { return Vec::new() };
+		let mut found: Vec<(u32, Vec<usize>)> = vec![(shortest_cost, shortest)];
+		let mut candidates = BinaryHeap::new();
+		let mut seen = HashSet::from([found[0].1.clone()]);
+
+		while found.len() < k {
+			let (_, previous) = found.last().unwrap().clone();
+			let campsites = self.campsites_along(start, &previous);
+
+			// Deviate from the previous route at every campsite along it, in turn
+			for (spur_index, &spur) in campsites.iter().enumerate().take(previous.len()) {
+				let root = &previous[..spur_index];
+
+				// Trails already used to leave the root of a found route are off limits, as are the
+				// campsites on the root itself, so the deviation is new and loopless.
+				let banned_trails: HashSet<usize> = found
+					.iter()
+					.filter(|(_, path)| path.len() > spur_index && path[..spur_index] == *root)
+					.map(|(_, path)| path[spur_index])
+					.collect();
+				let banned_campsites: HashSet<usize> =
+					campsites[..spur_index].iter().copied().collect();
+
+				let spur_paths = self.shortest_paths(spur, Some(destination), |trail| {
+					let (_, end) = self.endpoints(trail);
+					if banned_trails.contains(&trail) || banned_campsites.contains(&end) {
+						return None
+					}
+					cost(trail)
+				});
+				let Some(spur_path) = self.path_to(spur, destination, &spur_paths) else {
+					continue
+				};
+
+				let mut path = root.to_vec();
+				path.extend(spur_path);
+				let Some(path_cost) = path_cost(&path) else { continue };
+				if seen.insert(path.clone()) {
+					candidates.push(Reverse((path_cost, path)));
+				}
+			}
+
+			let Some(Reverse(next)) = candidates.pop() else { break };
+			found.push(next);
+		}
+
+		found
+			.into_iter()
+			.map(|(cost, path)| self.route_along(start, &path, cost))
+			.collect()
+	}
+
+	/// Returns the ids of the campsites visited when following the given trails from `start`.
+	fn campsites_along(&self, start: usize, trails: &[usize]) -> Vec<usize> {
+		let mut campsites = vec![start];
+		campsites.extend(trails.iter().map(|&trail| self.endpoints(trail).1));
+		campsites
+	}
+}
@@ -0,0 +1,150 @@
+//! Loading whole trail networks from text, one trail per line.
+//!
+//! Every line uses the same encoding as [`Trail`]'s `TryFrom<String>` implementation. In addition:
+//!
+//! * Blank lines, and lines starting with `#`, are skipped.
+//! * A line using `<=>` instead of `=>` describes a trail that can be hiked in either direction,
+//!   and is loaded as two one-way trails with the same distance, terrain and danger.
+//!
+//! Writing a [`TrailNetwork`] with `Display` produces a document in this format, with one `=>` line
+//! per trail.
+//!
+//! ### Example:
+//! ```text
+//! # The northern loop
+//! Mountain Top => Green Lake: 2000 (PavedTrail) [19]
+//! Green Lake <=> Prairie Meadows: 700 (UnpavedTrail) [30]
+//! ```
+
+use super::{ParseError, Trail, TrailNetwork, UnencodableCampsite};
+use std::{fmt, io::BufRead, str::FromStr};
+
+/// A line of a trail network or terrain profile document that could not be parsed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MalformedLine {
+	/// The line number, starting from 1.
+	pub line: usize,
+	/// The contents of the line.
+	pub text: String,
+	/// Why the line could not be parsed. Its offset is relative to the start of the line.
+	pub error: ParseError,
+}
+
+/// The reasons loading a line-based document, such as a trail network or a terrain profile, may
+/// fail.
+#[derive(Debug)]
+pub enum LoadError {
+	/// The document could not be read.
+	Io(std::io::Error),
+	/// Some lines of the document could not be parsed. Every malformed line is reported, in order.
+	Malformed(Vec<MalformedLine>),
+}
+
+impl fmt::Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			LoadError::Io(error) => write!(f, "could not read document: {error}"),
+			LoadError::Malformed(lines) => {
+				write!(f, "{} malformed line(s)", lines.len())?;
+				for malformed in lines {
+					write!(f, "\n  line {}: {}", malformed.line, malformed.error)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			LoadError::Io(error) => Some(error),
+			LoadError::Malformed(_) => None,
+		}
+	}
+}
+
+impl From<std::io::Error> for LoadError {
+	fn from(error: std::io::Error) -> Self {
+		LoadError::Io(error)
+	}
+}
+
+/// Reads a document line by line, skipping blank lines and lines starting with `#`, and passes
+/// every other line to `load`.
+///
+/// Reading stops at the first I/O error. Otherwise, the whole document is read and every line
+/// `load` rejects is reported together.
+pub(crate) fn load_lines(
+	reader: impl BufRead,
+	mut load: impl FnMut(&str) -> Result<(), ParseError>,
+) -> Result<(), LoadError> {
+	let mut malformed = Vec::new();
+
+	for (index, line) in reader.lines().enumerate() {
+		let line = line?;
+		let trimmed = line.trim();
+		if trimmed.is_empty() || trimmed.starts_with('#') {
+			continue
+		}
+		if let Err(error) = load(&line) {
+			malformed.push(MalformedLine { line: index + 1, text: line, error });
+		}
+	}
+
+	if malformed.is_empty() {
+		Ok(())
+	} else {
+		Err(LoadError::Malformed(malformed))
+	}
+}
+
+/// Parses a single line of a trail network document into the trails it describes.
+///
+/// `<=>` lines describe two trails, every other line one.
+fn parse_line(line: &str) -> Result<Vec<Trail>, ParseError> {
+	if line.contains("<=>") {
+		// Replacing with " =>" rather than "=>" keeps the error offsets aligned with the line
+		let there = Trail::try_from(line.replacen("<=>", " =>", 1))?;
+		let back = Trail { start: there.end.clone(), end: there.start.clone(), ..there.clone() };
+		return Ok(vec![there, back])
+	}
+
+	Ok(vec![Trail::try_from(line.to_string())?])
+}
+
+impl TrailNetwork {
+	/// Loads a trail network from any buffered reader, one trail per line.
+	///
+	/// Reading stops at the first I/O error. Otherwise, the whole document is read and every
+	/// malformed line is reported together.
+	pub fn from_reader(reader: impl BufRead) -> Result<TrailNetwork, LoadError> {
+		let mut network = TrailNetwork::new();
+		load_lines(reader, |line| {
+			network.extend(parse_line(line)?);
+			Ok(())
+		})?;
+		Ok(network)
+	}
+
+	/// Writes the network as a document it can be loaded from, like its `Display` implementation,
+	/// but refuses to write campsite names that would not parse back unchanged.
+	pub fn encode(&self) -> Result<String, UnencodableCampsite> {
+		self.trails().iter().map(|trail| Ok(trail.encode()? + "\n")).collect()
+	}
+}
+
+impl fmt::Display for TrailNetwork {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.trails().iter().try_for_each(|trail| writeln!(f, "{trail}"))
+	}
+}
+
+impl FromStr for TrailNetwork {
+	type Err = LoadError;
+
+	/// Loads a trail network from a multi-line document, one trail per line.
+	fn from_str(document: &str) -> Result<Self, Self::Err> {
+		TrailNetwork::from_reader(document.as_bytes())
+	}
+}
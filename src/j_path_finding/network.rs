@@ -0,0 +1,437 @@
+//! A trail network that is indexed once up front, so that it can be searched efficiently.
+
+use super::{Coordinates, Cost, CostOverflow, Hiker, Route, Schedule, Trail};
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+/// A network of one-way trails, indexed for fast path finding.
+///
+/// Campsite names are interned, so each campsite is identified internally by a small integer id,
+/// and the trails leaving every campsite are indexed so that a search only ever looks at the trails
+/// it can actually take. The network is meant to be built once and then queried many times, for
+/// any hiker and cost function, while trails are added or removed as the map changes.
+#[derive(Debug, Default, Clone)]
+pub struct TrailNetwork {
+	/// The name of every campsite, indexed by campsite id.
+	campsites: Vec<String>,
+	/// The id of every campsite, indexed by name.
+	ids: HashMap<String, usize>,
+	/// Every trail in the network, indexed by trail id.
+	trails: Vec<Trail>,
+	/// The start and end campsite ids of every trail, indexed by trail id.
+	endpoints: Vec<(usize, usize)>,
+	/// The ids of the trails leaving each campsite, indexed by campsite id.
+	outgoing: Vec<Vec<usize>>,
+	/// The ids of the trails arriving at each campsite, indexed by campsite id.
+	incoming: Vec<Vec<usize>>,
+	/// The location of each campsite, if known, indexed by campsite id.
+	coordinates: Vec<Option<Coordinates>>,
+	/// When each trail can be set out on, if it is not always open, indexed by trail id.
+	schedules: Vec<Option<Schedule>>,
+}
+
+/// The result of running Dijkstra's algorithm from a single campsite.
+pub(crate) struct ShortestPaths<C = u32> {
+	/// The minimal cost of reaching each campsite, if it was reached at all.
+	pub(crate) costs: Vec<Option<C>>,
+	/// The id of the trail used to reach each campsite on its cheapest route.
+	pub(crate) previous: Vec<Option<usize>>,
+	/// Whether any trail was skipped because the cost of getting to its end would overflow.
+	pub(crate) overflowed: bool,
+}
+
+impl TrailNetwork {
+	/// Returns the id of the campsite with the given name, adding it to the network if needed.
+	fn intern(&mut self, name: &str) -> usize {
+		if let Some(&id) = self.ids.get(name) {
+			return id
+		}
+
+		let id = self.campsites.len();
+		self.campsites.push(name.to_string());
+		self.ids.insert(name.to_string(), id);
+		self.outgoing.push(Vec::new());
+		self.incoming.push(Vec::new());
+		self.coordinates.push(None);
+		id
+	}
+
+	/// Creates an empty trail network.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a trail to the network.
+	pub fn add_trail(&mut self, trail: Trail) {
+		let start = self.intern(&trail.start);
+		let end = self.intern(&trail.end);
+		self.outgoing[start].push(self.trails.len());
+		self.incoming[end].push(self.trails.len());
+		self.endpoints.push((start, end));
+		self.schedules.push(None);
+		self.trails.push(trail);
+	}
+
+	/// Removes a trail equal to the given one from the network, returning whether there was one.
+	///
+	/// The campsites at either end of the trail stay in the network, even if no other trail
+	/// reaches them anymore.
+	pub fn remove_trail(&mut self, trail: &Trail) -> bool {
+		let Some(start) = self.campsite_id(&trail.start) else { return false };
+		let Some(position) = self.outgoing[start].iter().position(|&id| self.trails[id] == *trail)
+		else {
+			return false
+		};
+
+		let id = self.outgoing[start].swap_remove(position);
+		let (_, end) = self.endpoints[id];
+		self.incoming[end].retain(|&entry| entry != id);
+		self.trails.swap_remove(id);
+		self.endpoints.swap_remove(id);
+		self.schedules.swap_remove(id);
+
+		// The last trail was moved into the freed slot, so its index entries must follow it
+		let moved = self.trails.len();
+		if id != moved {
+			let (moved_start, moved_end) = self.endpoints[id];
+			for entry in self.outgoing[moved_start]
+				.iter_mut()
+				.chain(self.incoming[moved_end].iter_mut())
+				.filter(|entry| **entry == moved)
+			{
+				*entry = id;
+			}
+		}
+
+		true
+	}
+
+	/// All of the trails in the network, in no particular order.
+	pub fn trails(&self) -> &[Trail] {
+		&self.trails
+	}
+
+	/// The names of all of the campsites in the network, in no particular order.
+	pub fn campsites(&self) -> impl Iterator<Item = &str> {
+		self.campsites.iter().map(String::as_str)
+	}
+
+	/// Returns whether a campsite with the given name is part of the network.
+	pub fn contains_campsite(&self, name: &str) -> bool {
+		self.ids.contains_key(name)
+	}
+
+	/// Sets the location of a campsite, adding the campsite to the network if needed.
+	pub fn set_coordinates(&mut self, campsite: &str, coordinates: Coordinates) {
+		let id = self.intern(campsite);
+		self.coordinates[id] = Some(coordinates);
+	}
+
+	/// Returns the location of a campsite, if it is known.
+	pub fn coordinates(&self, campsite: &str) -> Option<Coordinates> {
+		self.coordinates[self.campsite_id(campsite)?]
+	}
+
+	/// Sets when every trail equal to the given one can be set out on, returning whether there was
+	/// such a trail. A trail without a schedule is always open.
+	pub fn set_schedule(&mut self, trail: &Trail, schedule: Option<Schedule>) -> bool {
+		let Some(start) = self.campsite_id(&trail.start) else { return false };
+		let mut found = false;
+		for &id in &self.outgoing[start] {
+			if self.trails[id] == *trail {
+				self.schedules[id] = schedule.clone();
+				found = true;
+			}
+		}
+		found
+	}
+
+	/// Returns the location of the campsite with the given id, if it is known.
+	pub(crate) fn coordinates_of(&self, campsite: usize) -> Option<Coordinates> {
+		self.coordinates[campsite]
+	}
+
+	/// Returns the id of the campsite with the given name, if it is part of the network.
+	pub(crate) fn campsite_id(&self, name: &str) -> Option<usize> {
+		self.ids.get(name).copied()
+	}
+
+	/// Returns the name of the campsite with the given id.
+	pub(crate) fn campsite_name(&self, id: usize) -> &str {
+		&self.campsites[id]
+	}
+
+	/// Returns the trail with the given id.
+	pub(crate) fn trail(&self, id: usize) -> &Trail {
+		&self.trails[id]
+	}
+
+	/// Returns the start and end campsite ids of the trail with the given id.
+	pub(crate) fn endpoints(&self, trail: usize) -> (usize, usize) {
+		self.endpoints[trail]
+	}
+
+	/// Returns the schedule of the trail with the given id, if it has one.
+	pub(crate) fn schedule(&self, trail: usize) -> Option<&Schedule> {
+		self.schedules[trail].as_ref()
+	}
+
+	/// Returns the ids of the trails leaving the campsite with the given id.
+	pub(crate) fn outgoing(&self, campsite: usize) -> &[usize] {
+		&self.outgoing[campsite]
+	}
+
+	/// Returns the ids of the trails arriving at the campsite with the given id.
+	pub(crate) fn incoming(&self, campsite: usize) -> &[usize] {
+		&self.incoming[campsite]
+	}
+
+	/// The number of distinct campsites in the network.
+	pub fn campsite_count(&self) -> usize {
+		self.campsites.len()
+	}
+
+	/// Finds the cheapest route from `start` to `destination`, like [`super::optimal_path`].
+	///
+	/// Routes whose cost does not fit in a `u32` are never returned. Use
+	/// [`Self::try_optimal_route`] to tell them apart from a destination that cannot be reached.
+	pub fn optimal_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<Route> {
+		self.cheapest_route(start, destination, |trail| cost_function(hiker, trail))
+	}
+
+	/// Like [`Self::optimal_route`], but with costs of any [`Cost`] type, such as `u64` for long
+	/// and costly routes.
+	///
+	/// Returns an error if the destination can be reached, but only by routes whose cost does not
+	/// fit in the cost type.
+	pub fn try_optimal_route<C: Cost>(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<C>,
+	) -> Result<Option<Route<C>>, CostOverflow> {
+		self.try_cheapest_route(start, destination, |trail| cost_function(hiker, trail))
+	}
+
+	/// Finds the cheapest route from `start` to `destination`, for a cost that does not depend on a
+	/// single hiker.
+	pub(crate) fn cheapest_route(
+		&self,
+		start: &str,
+		destination: &str,
+		cost: impl Fn(&Trail) -> Option<u32>,
+	) -> Option<Route> {
+		self.try_cheapest_route(start, destination, cost).ok().flatten()
+	}
+
+	/// Like [`Self::cheapest_route`], but with costs of any type, reporting routes that overflow.
+	pub(crate) fn try_cheapest_route<C: Cost>(
+		&self,
+		start: &str,
+		destination: &str,
+		cost: impl Fn(&Trail) -> Option<C>,
+	) -> Result<Option<Route<C>>, CostOverflow> {
+		if start == destination {
+			let campsites = vec![start.to_string()];
+			return Ok(Some(Route { trails: Vec::new(), campsites, cost: C::default() }))
+		}
+
+		let (Some(start_id), Some(destination_id)) =
+			(self.campsite_id(start), self.campsite_id(destination))
+		else {
+			return Ok(None)
+		};
+		let paths =
+			self.shortest_paths(start_id, Some(destination_id), |id| cost(&self.trails[id]));
+		if let Some(route) = self.route_to(start_id, destination_id, &paths) {
+			return Ok(Some(route))
+		}
+
+		// Skipping the trails that overflowed may be the only reason the destination was missed
+		if paths.overflowed &&
+			self.can_reach(start_id, destination_id, |id| cost(&self.trails[id]).is_some())
+		{
+			return Err(CostOverflow {
+				start: start.to_string(),
+				destination: destination.to_string(),
+			})
+		}
+		Ok(None)
+	}
+
+	/// Returns whether `destination` can be reached from `start` at all, using only the trails
+	/// that are `usable`.
+	fn can_reach(&self, start: usize, destination: usize, usable: impl Fn(usize) -> bool) -> bool {
+		let mut seen = vec![false; self.campsites.len()];
+		seen[start] = true;
+		let mut queue = VecDeque::from([start]);
+		while let Some(current) = queue.pop_front() {
+			if current == destination {
+				return true
+			}
+			for &trail in &self.outgoing[current] {
+				let (_, end) = self.endpoints[trail];
+				if usable(trail) && !seen[end] {
+					seen[end] = true;
+					queue.push_back(end);
+				}
+			}
+		}
+		false
+	}
+
+	/// Like [`Self::optimal_route`], but only returns the cost of the route.
+	pub fn optimal_path(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<u32> {
+		self.optimal_route(start, destination, hiker, cost_function)
+			.map(|route| route.cost)
+	}
+
+	/// Like [`Self::try_optimal_route`], but only returns the cost of the route.
+	pub fn try_optimal_path<C: Cost>(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<C>,
+	) -> Result<Option<C>, CostOverflow> {
+		let route = self.try_optimal_route(start, destination, hiker, cost_function)?;
+		Ok(route.map(|route| route.cost))
+	}
+
+	/// Runs Dijkstra's algorithm from `start`, using a binary heap as the priority queue.
+	///
+	/// `cost` is given a trail id and returns the cost of traversing that trail, if possible. If a
+	/// `destination` is given, the search stops as soon as its cost is known.
+	///
+	/// Trails that would take the cost of a route past what `C` can represent are skipped, as if
+	/// they could not be traversed. This never hides a route whose cost does fit.
+	pub(crate) fn shortest_paths<C: Cost>(
+		&self,
+		start: usize,
+		destination: Option<usize>,
+		cost: impl Fn(usize) -> Option<C>,
+	) -> ShortestPaths<C> {
+		self.time_dependent_paths(start, destination, |trail, _| cost(trail))
+	}
+
+	/// Like [`Self::shortest_paths`], but `cost` is also given the cost of reaching the start of
+	/// the trail, so that the cost of a trail can depend on when it is set out on, or whether it
+	/// would go over a budget.
+	///
+	/// The costs found are minimal as long as setting out on a trail later never means reaching
+	/// its end earlier.
+	pub(crate) fn time_dependent_paths<C: Cost>(
+		&self,
+		start: usize,
+		destination: Option<usize>,
+		cost: impl Fn(usize, C) -> Option<C>,
+	) -> ShortestPaths<C> {
+		let mut costs = vec![None; self.campsites.len()];
+		let mut previous = vec![None; self.campsites.len()];
+		let mut visited = vec![false; self.campsites.len()];
+		let mut overflowed = false;
+		let mut queue = BinaryHeap::new();
+
+		costs[start] = Some(C::default());
+		queue.push(Reverse((C::default(), start)));
+
+		while let Some(Reverse((current_cost, current))) = queue.pop() {
+			// Stale entries are left in the queue rather than updated in place
+			if visited[current] {
+				continue
+			}
+			visited[current] = true;
+
+			if Some(current) == destination {
+				break
+			}
+
+			for &trail in &self.outgoing[current] {
+				let Some(trail_cost) = cost(trail, current_cost) else { continue };
+				let (_, end) = self.endpoints[trail];
+				let Some(new_cost) = current_cost.checked_add(trail_cost) else {
+					overflowed = true;
+					continue
+				};
+				if costs[end].is_none_or(|known| new_cost < known) {
+					costs[end] = Some(new_cost);
+					previous[end] = Some(trail);
+					queue.push(Reverse((new_cost, end)));
+				}
+			}
+		}
+
+		ShortestPaths { costs, previous, overflowed }
+	}
+
+	/// Rebuilds the route to `destination` from the result of a search started at `start`.
+	pub(crate) fn route_to<C: Cost>(
+		&self,
+		start: usize,
+		destination: usize,
+		paths: &ShortestPaths<C>,
+	) -> Option<Route<C>> {
+		let cost = paths.costs[destination]?;
+		let trails = self.path_to(start, destination, paths)?;
+		Some(self.route_along(start, &trails, cost))
+	}
+
+	/// Returns the ids of the trails on the route to `destination`, from the result of a search
+	/// started at `start`.
+	pub(crate) fn path_to<C>(
+		&self,
+		start: usize,
+		destination: usize,
+		paths: &ShortestPaths<C>,
+	) -> Option<Vec<usize>> {
+		paths.costs[destination].as_ref()?;
+
+		let mut trails = Vec::new();
+		let mut current = destination;
+		while current != start {
+			let trail = paths.previous[current]?;
+			trails.push(trail);
+			current = self.endpoints[trail].0;
+		}
+		trails.reverse();
+		Some(trails)
+	}
+
+	/// Builds the route from `start` that follows the trails with the given ids, in order.
+	pub(crate) fn route_along<C>(&self, start: usize, trails: &[usize], cost: C) -> Route<C> {
+		let trails: Vec<Trail> = trails.iter().map(|&id| self.trails[id].clone()).collect();
+		let mut campsites = vec![self.campsites[start].clone()];
+		campsites.extend(trails.iter().map(|trail| trail.end.clone()));
+
+		Route { trails, campsites, cost }
+	}
+}
+
+impl FromIterator<Trail> for TrailNetwork {
+	fn from_iter<I: IntoIterator<Item = Trail>>(trails: I) -> Self {
+		let mut network = TrailNetwork::new();
+		network.extend(trails);
+		network
+	}
+}
+
+impl Extend<Trail> for TrailNetwork {
+	fn extend<I: IntoIterator<Item = Trail>>(&mut self, trails: I) {
+		trails.into_iter().for_each(|trail| self.add_trail(trail));
+	}
+}
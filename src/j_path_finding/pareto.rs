@@ -0,0 +1,159 @@
+//! Multi-criteria route search.
+//!
+//! Rather than optimizing a single cost, this finds the Pareto frontier of routes under several
+//! cost functions at once: every route for which no other route is at least as good under every
+//! cost function. This lets hikers weigh up tradeoffs such as "20% longer but half the danger".
+
+use super::{CostFunction, Hiker, Route, TrailNetwork};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// A partial route found during the search, ending at `campsite`.
+struct Label {
+	costs: Vec<u32>,
+	campsite: usize,
+	/// The label this one extends, and the id of the trail that extends it.
+	previous: Option<(usize, usize)>,
+}
+
+/// Returns whether costs `a` are at least as good as costs `b` under every cost function.
+fn dominates(a: &[u32], b: &[u32]) -> bool {
+	a.iter().zip(b).all(|(a, b)| a <= b)
+}
+
+impl TrailNetwork {
+	/// Finds every Pareto optimal route from `start` to `destination`.
+	///
+	/// A trail can only be traversed if every cost function returns `Some` for it. The routes are
+	/// returned in lexicographic order of their costs, so the first route is also the optimal
+	/// route under the first cost function. When several routes have exactly the same costs, only
+	/// one of them is returned.
+	///
+	/// Each route's cost holds its total under each cost function, in the order they were given.
+	pub fn pareto_routes(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_functions: &[CostFunction],
+	) -> Vec<Route<Vec<u32>>> {
+		let (Some(start), Some(destination)) =
+			(self.campsite_id(start), self.campsite_id(destination))
+		else {
+			return Vec::new()
+		};
+
+		self.label_search(start, destination, hiker, cost_functions, |_, _| true, false)
+	}
+
+	/// Runs a multi-criteria generalization of Dijkstra's algorithm, returning the Pareto optimal
+	/// routes in lexicographic order of their costs.
+	///
+	/// `is_feasible` is given the costs of a trail and the total costs of a partial route ending
+	/// with it, and decides whether the partial route may be used at all. If `first_only` is set,
+	/// the search stops as soon as the lexicographically smallest route is found.
+	pub(crate) fn label_search(
+		&self,
+		start: usize,
+		destination: usize,
+		hiker: &Hiker,
+		cost_functions: &[CostFunction],
+		is_feasible: impl Fn(&[u32], &[u32]) -> bool,
+		first_only: bool,
+	) -> Vec<Route<Vec<u32>>> {
+		// Labels are settled in lexicographic order of their costs, so any label that could
+		// dominate another one is always settled before it.
+		let mut labels =
+			vec![Label { costs: vec![0; cost_functions.len()], campsite: start, previous: None }];
+		let mut settled: Vec<Vec<usize>> = vec![Vec::new(); self.campsite_count()];
+		let mut queue = BinaryHeap::new();
+		queue.push(Reverse((labels[0].costs.clone(), 0)));
+
+		let is_dominated = |settled: &[usize], labels: &[Label], costs: &[u32]| {
+			settled.iter().any(|&label| dominates(&labels[label].costs, costs))
+		};
+
+		while let Some(Reverse((costs, label))) = queue.pop() {
+			let campsite = labels[label].campsite;
+			if is_dominated(&settled[campsite], &labels, &costs) {
+				continue
+			}
+			settled[campsite].push(label);
+
+			if campsite == destination {
+				if first_only {
+					break
+				}
+				continue
+			}
+
+			for &trail in self.outgoing(campsite) {
+				let Some(trail_costs) = cost_functions
+					.iter()
+					.map(|cost_function| cost_function(hiker, self.trail(trail)))
+					.collect::<Option<Vec<u32>>>()
+				else {
+					continue
+				};
+
+				let (_, end) = self.endpoints(trail);
+				let Some(new_costs) = costs
+					.iter()
+					.zip(&trail_costs)
+					.map(|(a, b)| a.checked_add(*b))
+					.collect::<Option<Vec<u32>>>()
+				else {
+					continue
+				};
+				if !is_feasible(&trail_costs, &new_costs) ||
+					is_dominated(&settled[end], &labels, &new_costs)
+				{
+					continue
+				}
+
+				labels.push(Label {
+					costs: new_costs.clone(),
+					campsite: end,
+					previous: Some((label, trail)),
+				});
+				queue.push(Reverse((new_costs, labels.len() - 1)));
+			}
+		}
+
+		settled[destination]
+			.iter()
+			.map(|&label| {
+				let mut trails = Vec::new();
+				let mut current = label;
+				while let Some((previous, trail)) = labels[current].previous {
+					trails.push(trail);
+					current = previous;
+				}
+				trails.reverse();
+
+				self.route_along(start, &trails, labels[label].costs.clone())
+			})
+			.collect()
+	}
+
+	/// Finds every Pareto optimal route from `start` to `destination` under the three classic
+	/// objectives: distance, travel time and danger, in that order.
+	///
+	/// Only trails the hiker can actually traverse are considered.
+	pub fn tradeoff_routes(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+	) -> Vec<Route<Vec<u32>>> {
+		self.pareto_routes(
+			start,
+			destination,
+			hiker,
+			&[
+				&|_, trail| Some(trail.distance),
+				&|hiker, trail| hiker.travel_time(&trail.terrain, trail.distance),
+				&|_, trail| Some(trail.danger as u32),
+			],
+		)
+	}
+}
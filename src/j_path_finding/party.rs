@@ -0,0 +1,78 @@
+//! Group hiking, where a party of hikers travels together.
+
+use super::{Hiker, Objective, Route, Terrain, Trail, TrailNetwork};
+
+/// A group of hikers that travel together.
+///
+/// A party can only traverse a trail if every member can, and moves at the pace of its slowest
+/// member on each trail. A party with no members cannot traverse anything.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Party {
+	pub members: Vec<Hiker>,
+}
+
+impl Party {
+	/// Returns the time it takes the whole party to traverse a trail, which is the time its slowest
+	/// member takes, if every member can traverse it at all.
+	pub fn travel_time(&self, terrain: &Terrain, distance: u32) -> Option<u32> {
+		self.members
+			.iter()
+			.map(|member| member.travel_time(terrain, distance))
+			.collect::<Option<Vec<u32>>>()?
+			.into_iter()
+			.max()
+	}
+
+	/// Returns whether every member of the party can traverse the trail.
+	pub fn can_traverse(&self, trail: &Trail) -> bool {
+		self.travel_time(&trail.terrain, trail.distance).is_some()
+	}
+
+	/// The cost of the party traversing a trail under the given objective, if every member can
+	/// traverse it.
+	///
+	/// Unlike for a single hiker, trails the party cannot traverse are ruled out under every
+	/// objective. The cost is the highest of the members' costs, so custom objectives are also
+	/// paced by the slowest member.
+	pub fn cost(&self, objective: &Objective, trail: &Trail) -> Option<u32> {
+		// Travel time objectives already rule out trails a member cannot traverse
+		let checks_feasibility =
+			matches!(objective, Objective::TravelTime | Objective::TravelTimeWith(_));
+
+		self.members
+			.iter()
+			.map(|member| {
+				if !checks_feasibility && !trail.terrain.rules().allows(member) {
+					return None
+				}
+				objective.cost(member, trail)
+			})
+			.collect::<Option<Vec<u32>>>()?
+			.into_iter()
+			.max()
+	}
+}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination` that every member of the party can
+	/// traverse, under the given objective.
+	pub fn party_route(
+		&self,
+		start: &str,
+		destination: &str,
+		party: &Party,
+		objective: Objective,
+	) -> Option<Route> {
+		self.cheapest_route(start, destination, |trail| party.cost(&objective, trail))
+	}
+
+	/// Finds the fastest route from `start` to `destination` for the whole party.
+	pub fn fastest_party_route(
+		&self,
+		start: &str,
+		destination: &str,
+		party: &Party,
+	) -> Option<Route> {
+		self.party_route(start, destination, party, Objective::TravelTime)
+	}
+}
@@ -0,0 +1,106 @@
+//! A builder for point-to-point route queries.
+//!
+//! [`RouteQuery`] gathers everything a route search needs in one place: where to start, where to
+//! go, who is hiking, and what to optimize for. The problem-specific helpers in this module are all
+//! expressed through it, so that none of them can quietly ignore one of their arguments.
+
+use super::{optimal_route, CostFunction, Hiker, Route, TerrainProfile, Trail, TrailNetwork};
+
+/// What a route search should minimize.
+#[derive(Clone, Copy)]
+pub enum Objective<'a> {
+	/// The total distance, whether or not the hiker can traverse every trail.
+	Distance,
+	/// The total travel time of the hiker, using only trails they can traverse.
+	TravelTime,
+	/// Like [`Objective::TravelTime`], but with the rates from the given profile.
+	TravelTimeWith(&'a TerrainProfile),
+	/// The total danger, whether or not the hiker can traverse every trail.
+	Danger,
+	/// Any other cost function.
+	Custom(CostFunction<'a>),
+}
+
+impl Objective<'_> {
+	/// The cost of the hiker traversing a trail under this objective, if they can traverse it.
+	pub fn cost(&self, hiker: &Hiker, trail: &Trail) -> Option<u32> {
+		match self {
+			Objective::Distance => Some(trail.distance),
+			Objective::TravelTime => hiker.travel_time(&trail.terrain, trail.distance),
+			Objective::TravelTimeWith(profile) =>
+				hiker.travel_time_with(profile, &trail.terrain, trail.distance),
+			Objective::Danger => Some(trail.danger as u32),
+			Objective::Custom(cost_function) => cost_function(hiker, trail),
+		}
+	}
+}
+
+/// A route search from one campsite to another.
+///
+/// Unless told otherwise, the query is for Bill, the default hiker, and minimizes distance.
+///
+/// ### Example:
+/// ```
+/// # use pba_entrance_exam::j_path_finding::*;
+/// let hiker = Hiker::default();
+/// let trail = Trail::try_from("Green Lake => Prairie Meadows: 700 (Water) [3]".to_string());
+/// let route = RouteQuery::new("Green Lake", "Prairie Meadows")
+///     .hiker(&hiker)
+///     .objective(Objective::TravelTime)
+///     .find(trail.into_iter());
+/// assert_eq!(route.map(|route| route.cost), Some(2100));
+/// ```
+#[derive(Clone)]
+pub struct RouteQuery<'a> {
+	start: String,
+	destination: String,
+	hiker: Option<&'a Hiker>,
+	objective: Objective<'a>,
+}
+
+impl<'a> RouteQuery<'a> {
+	/// Creates a query for a route from `start` to `destination`.
+	pub fn new(start: impl Into<String>, destination: impl Into<String>) -> Self {
+		Self {
+			start: start.into(),
+			destination: destination.into(),
+			hiker: None,
+			objective: Objective::Distance,
+		}
+	}
+
+	/// Sets the hiker the route is for.
+	pub fn hiker(mut self, hiker: &'a Hiker) -> Self {
+		self.hiker = Some(hiker);
+		self
+	}
+
+	/// Sets what the route should minimize.
+	pub fn objective(mut self, objective: Objective<'a>) -> Self {
+		self.objective = objective;
+		self
+	}
+
+	/// Finds the optimal route through the given trails.
+	pub fn find(&self, trails: impl Iterator<Item = Trail>) -> Option<Route> {
+		let bill = Hiker::default();
+		optimal_route(
+			self.start.clone(),
+			self.destination.clone(),
+			self.hiker.unwrap_or(&bill),
+			trails,
+			|hiker, trail| self.objective.cost(hiker, trail),
+		)
+	}
+
+	/// Finds the optimal route through a network that has already been built.
+	pub fn find_in(&self, network: &TrailNetwork) -> Option<Route> {
+		let bill = Hiker::default();
+		network.optimal_route(
+			&self.start,
+			&self.destination,
+			self.hiker.unwrap_or(&bill),
+			|hiker, trail| self.objective.cost(hiker, trail),
+		)
+	}
+}
@@ -0,0 +1,39 @@
+//! Reachability and isochrone queries, such as "where can a beginner get within 2 hours of the
+//! visitor center?".
+
+use super::{Hiker, Trail, TrailNetwork};
+
+impl TrailNetwork {
+	/// Finds every campsite a hiker can reach from `start` for a total cost of at most `budget`,
+	/// along with the minimal cost of reaching it, cheapest first.
+	///
+	/// The start itself is always included, at a cost of 0, as long as it is part of the network.
+	/// Campsites that cost the same are sorted by name. The search never looks past the budget, so
+	/// a small budget only explores the neighbourhood of the start.
+	pub fn reachable_within(
+		&self,
+		start: &str,
+		hiker: &Hiker,
+		budget: u32,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Vec<(String, u32)> {
+		let Some(start) = self.campsite_id(start) else { return Vec::new() };
+
+		// Trails that would go over the budget are treated as impassable
+		let paths = self.time_dependent_paths(start, None, |trail, reached: u32| {
+			let cost = cost_function(hiker, self.trail(trail))?;
+			reached.checked_add(cost).filter(|&total| total <= budget).map(|_| cost)
+		});
+
+		let mut reachable: Vec<(String, u32)> = paths
+			.costs
+			.iter()
+			.enumerate()
+			.filter_map(|(id, cost)| Some((self.campsite_name(id).to_string(), (*cost)?)))
+			.collect();
+		reachable.sort_by(|(a_name, a_cost), (b_name, b_cost)| {
+			a_cost.cmp(b_cost).then_with(|| a_name.cmp(b_name))
+		});
+		reachable
+	}
+}
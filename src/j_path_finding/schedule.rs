@@ -0,0 +1,133 @@
+//! Trails that are only open at certain times, such as ferries, or rope bridges closed at night.
+//!
+//! A trail can be given a [`Schedule`] with [`TrailNetwork::set_schedule`], listing the windows in
+//! which it can be set out on. Times are plain numbers in the same unit as the cost function, for
+//! example minutes since midnight on the first day of the trip, so a schedule that repeats every
+//! day has a period of 1440.
+//!
+//! [`TrailNetwork::earliest_arrival`] then finds when a hiker can reach their destination at the
+//! earliest, waiting at a campsite whenever the next trail is closed. As waiting is always
+//! allowed, setting out later never gets a hiker anywhere sooner, so Dijkstra's algorithm still
+//! finds the earliest arrival when trail costs depend on the time of day.
+
+use super::{Hiker, Route, Trail, TrailNetwork};
+
+/// A span of time in which a trail can be set out on, including both ends.
+///
+/// A ferry that leaves at a fixed time has a window that opens and closes at that time.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Window {
+	pub opens: u32,
+	pub closes: u32,
+}
+
+/// When a trail can be set out on.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Schedule {
+	/// The windows in which the trail is open, sorted by when they open.
+	windows: Vec<Window>,
+	/// How often the windows repeat, if they do at all.
+	period: Option<u32>,
+}
+
+impl Schedule {
+	/// A schedule that is open in the given windows only, for example for a trail closed for the
+	/// rest of the season.
+	pub fn once(windows: impl IntoIterator<Item = Window>) -> Self {
+		let mut windows: Vec<Window> = windows.into_iter().collect();
+		windows.sort_by_key(|window| window.opens);
+		Self { windows, period: None }
+	}
+
+	/// A schedule that repeats every `period`, such as daily opening hours. The windows are given
+	/// for the first period, so they must close before `period`. A period of zero is never open.
+	pub fn repeating(period: u32, windows: impl IntoIterator<Item = Window>) -> Self {
+		Self { period: Some(period), ..Self::once(windows) }
+	}
+
+	/// Returns the earliest time, at or after `time`, when the trail can be set out on.
+	pub fn next_departure(&self, time: u32) -> Option<u32> {
+		let within = |time: u32| {
+			self.windows
+				.iter()
+				.find(|window| window.closes >= time)
+				.map(|window| window.opens.max(time))
+		};
+
+		match self.period {
+			None => within(time),
+			Some(period) => {
+				let offset = time.checked_rem(period)?;
+				let start_of_period = time - offset;
+				let next_period = start_of_period.checked_add(period)?;
+				match within(offset) {
+					Some(departure) => start_of_period.checked_add(departure),
+					None => next_period.checked_add(self.windows.first()?.opens),
+				}
+			},
+		}
+	}
+}
+
+/// The result of an earliest arrival search.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Journey {
+	/// The trails to follow. Its cost is the time from setting out to arriving, including any time
+	/// spent waiting for trails to open.
+	pub route: Route,
+	/// The time each trail on the route is set out on, in order.
+	pub departures: Vec<u32>,
+	/// The time the destination is reached.
+	pub arrival: u32,
+}
+
+impl TrailNetwork {
+	/// Finds the earliest a hiker leaving `start` at `departure` can reach `destination`, taking
+	/// the trails' schedules into account.
+	///
+	/// The cost function gives the time it takes to traverse a trail once the hiker has set out on
+	/// it, such as [`Hiker::travel_time`]. Trails without a schedule are always open.
+	pub fn earliest_arrival(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		departure: u32,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<u32>,
+	) -> Option<Journey> {
+		let start = self.campsite_id(start)?;
+		let destination = self.campsite_id(destination)?;
+
+		// When a hiker at the start of a trail at `now` can set out on it
+		let set_out = |trail: usize, now: u32| match self.schedule(trail) {
+			Some(schedule) => schedule.next_departure(now),
+			None => Some(now),
+		};
+		let arrival = |trail: usize, now: u32| {
+			set_out(trail, now)?.checked_add(cost_function(hiker, self.trail(trail))?)
+		};
+
+		let paths = self.time_dependent_paths(start, Some(destination), |trail, elapsed| {
+			let now = departure.checked_add(elapsed)?;
+			Some(arrival(trail, now)? - now)
+		});
+		let elapsed = paths.costs[destination]?;
+		let trails = self.path_to(start, destination, &paths)?;
+
+		// Replay the route to recover when each trail was set out on
+		let departures = trails
+			.iter()
+			.scan(departure, |now, &trail| {
+				let departure = set_out(trail, *now)?;
+				*now = arrival(trail, *now)?;
+				Some(departure)
+			})
+			.collect();
+
+		Some(Journey {
+			route: self.route_along(start, &trails, elapsed),
+			departures,
+			arrival: departure + elapsed,
+		})
+	}
+}
@@ -0,0 +1,187 @@
+//! Route searches with signed costs, for objectives that reward some trails, such as a scenic
+//! score.
+//!
+//! Dijkstra's algorithm is only correct when no trail has a negative cost, so as soon as one does,
+//! the search falls back to the Bellman–Ford algorithm. A cycle of trails whose costs add up to
+//! less than zero could be walked forever to make a route ever cheaper. Rather than loop forever,
+//! the search reports such a cycle as an error, naming its campsites, whenever the destination
+//! can be reached from it.
+
+use super::{Hiker, Route, Trail, TrailNetwork};
+use std::{collections::VecDeque, fmt};
+
+/// The error returned when a route could be made cheaper without limit, by going around a cycle
+/// whose cost is negative.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NegativeCycle {
+	/// The trails around the cycle, in order.
+	pub trails: Vec<Trail>,
+	/// The campsites around the cycle, starting and ending at the same one.
+	pub campsites: Vec<String>,
+	/// The total cost of going around the cycle once, which is below zero.
+	pub cost: i64,
+}
+
+impl fmt::Display for NegativeCycle {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "negative cycle with a cost of {}: {}", self.cost, self.campsites.join(" => "))
+	}
+}
+
+impl std::error::Error for NegativeCycle {}
+
+impl TrailNetwork {
+	/// Finds the cheapest route from `start` to `destination` when trails may have negative costs.
+	///
+	/// Returns `Ok(None)` if the destination cannot be reached, and an error if a negative cycle
+	/// can be reached from the start and leads on to the destination, as no route would then be
+	/// the cheapest.
+	pub fn signed_route(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<i64>,
+	) -> Result<Option<Route<i64>>, NegativeCycle> {
+		let (Some(start_id), Some(destination_id)) =
+			(self.campsite_id(start), self.campsite_id(destination))
+		else {
+			let trivial = Route { trails: Vec::new(), campsites: vec![start.to_string()], cost: 0 };
+			return Ok((start == destination).then_some(trivial))
+		};
+		let costs: Vec<Option<i64>> =
+			self.trails().iter().map(|trail| cost_function(hiker, trail)).collect();
+
+		// Without negative costs, Dijkstra's algorithm is correct and much faster. Summing `u32`
+		// costs as `u64` cannot overflow on any route without a repeated trail.
+		if costs.iter().flatten().all(|&cost| u32::try_from(cost).is_ok()) {
+			let paths = self.shortest_paths(start_id, Some(destination_id), |trail| {
+				costs[trail].map(|cost| cost as u64)
+			});
+			return Ok(self.route_to(start_id, destination_id, &paths).map(|route| Route {
+				trails: route.trails,
+				campsites: route.campsites,
+				cost: route.cost as i64,
+			}))
+		}
+
+		self.bellman_ford(start_id, destination_id, &costs)
+	}
+
+	/// Like [`Self::signed_route`], but only returns the cost of the route.
+	pub fn signed_path(
+		&self,
+		start: &str,
+		destination: &str,
+		hiker: &Hiker,
+		cost_function: impl Fn(&Hiker, &Trail) -> Option<i64>,
+	) -> Result<Option<i64>, NegativeCycle> {
+		let route = self.signed_route(start, destination, hiker, cost_function)?;
+		Ok(route.map(|route| route.cost))
+	}
+
+	/// Runs the Bellman–Ford algorithm from `start`, given the cost of every trail by id.
+	fn bellman_ford(
+		&self,
+		start: usize,
+		destination: usize,
+		costs: &[Option<i64>],
+	) -> Result<Option<Route<i64>>, NegativeCycle> {
+		let count = self.campsite_count();
+		let mut best: Vec<Option<i64>> = vec![None; count];
+		let mut previous: Vec<Option<usize>> = vec![None; count];
+		best[start] = Some(0);
+
+		// Without negative cycles, every cheapest route is found within `count - 1` rounds, so a
+		// campsite that still gets cheaper in the round after that is reachable from one
+		let mut updated = Vec::new();
+		for _ in 0..count {
+			updated.clear();
+			for (trail, cost) in costs.iter().enumerate() {
+				let Some(cost) = cost else { continue };
+				let (trail_start, trail_end) = self.endpoints(trail);
+				let Some(new_cost) = best[trail_start].and_then(|known| known.checked_add(*cost))
+				else {
+					continue
+				};
+				if best[trail_end].is_none_or(|known| new_cost < known) {
+					best[trail_end] = Some(new_cost);
+					previous[trail_end] = Some(trail);
+					updated.push(trail_end);
+				}
+			}
+			if updated.is_empty() {
+				break
+			}
+		}
+
+		if !updated.is_empty() {
+			let leads_to_destination = self.campsites_leading_to(destination, costs);
+			let cycle = updated
+				.iter()
+				.filter(|&&campsite| leads_to_destination[campsite])
+				.find_map(|&campsite| self.cycle_before(campsite, &previous));
+			if let Some(cycle) = cycle {
+				let cost = cycle
+					.iter()
+					.map(|&trail| costs[trail].unwrap_or_default())
+					.fold(0i64, i64::saturating_add);
+				let route = self.route_along(self.endpoints(cycle[0]).0, &cycle, cost);
+				return Err(NegativeCycle { trails: route.trails, campsites: route.campsites, cost })
+			}
+		}
+
+		let Some(cost) = best[destination] else { return Ok(None) };
+		let mut trails = Vec::new();
+		let mut current = destination;
+		while current != start {
+			let Some(trail) = previous[current] else { return Ok(None) };
+			trails.push(trail);
+			current = self.endpoints(trail).0;
+		}
+		trails.reverse();
+
+		Ok(Some(self.route_along(start, &trails, cost)))
+	}
+
+	/// Returns, for every campsite, whether `destination` can be reached from it using trails that
+	/// have a cost.
+	fn campsites_leading_to(&self, destination: usize, costs: &[Option<i64>]) -> Vec<bool> {
+		let mut leads = vec![false; self.campsite_count()];
+		leads[destination] = true;
+		let mut queue = VecDeque::from([destination]);
+		while let Some(current) = queue.pop_front() {
+			for &trail in self.incoming(current) {
+				let (trail_start, _) = self.endpoints(trail);
+				if costs[trail].is_some() && !leads[trail_start] {
+					leads[trail_start] = true;
+					queue.push_back(trail_start);
+				}
+			}
+		}
+		leads
+	}
+
+	/// Returns the trail ids around the cycle that the cheapest known way to `campsite` comes
+	/// from, by following the trails it was reached by backwards until a campsite repeats.
+	fn cycle_before(&self, campsite: usize, previous: &[Option<usize>]) -> Option<Vec<usize>> {
+		// Going back once per campsite is sure to end up on the cycle itself
+		let mut on_cycle = campsite;
+		for _ in 0..self.campsite_count() {
+			on_cycle = self.endpoints(previous[on_cycle]?).0;
+		}
+
+		let mut cycle = Vec::new();
+		let mut current = on_cycle;
+		loop {
+			let trail = previous[current]?;
+			cycle.push(trail);
+			current = self.endpoints(trail).0;
+			if current == on_cycle {
+				break
+			}
+		}
+		cycle.reverse();
+		Some(cycle)
+	}
+}
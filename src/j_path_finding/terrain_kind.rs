@@ -0,0 +1,173 @@
+//! Terrain kinds beyond the built-in ones, such as snow fields, scrambles or ferries.
+//!
+//! A new kind of terrain is described by a [`TerrainKind`] and registered once with
+//! [`Terrain::register`]. From then on, it parses from its name like any built-in terrain, both
+//! on its own and as part of a trail, and hikers can traverse it according to its [`TerrainRules`],
+//! rates and fixed time.
+
+use super::{Hiker, Rate, Skill, Terrain};
+use std::{fmt, sync::Mutex};
+
+/// Which of a hiker's skills determines how fast they traverse a terrain.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Discipline {
+	Hiking,
+	Swimming,
+}
+
+/// The requirements a hiker must meet to traverse a terrain, and the skill that sets their pace.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TerrainRules {
+	/// Whether only brave hikers can traverse the terrain.
+	pub requires_brave: bool,
+	/// Whether only strong hikers can traverse the terrain.
+	pub requires_strong: bool,
+	/// The skill that determines the hiker's travel time.
+	pub discipline: Discipline,
+}
+
+impl TerrainRules {
+	/// Rules for terrain anyone can hike.
+	pub const HIKING: TerrainRules = TerrainRules {
+		requires_brave: false,
+		requires_strong: false,
+		discipline: Discipline::Hiking,
+	};
+
+	/// Returns whether the hiker meets the requirements.
+	pub fn allows(&self, hiker: &Hiker) -> bool {
+		(hiker.brave || !self.requires_brave) && (hiker.strong || !self.requires_strong)
+	}
+
+	/// Returns the hiker's skill level in this terrain's discipline.
+	pub fn skill<'a>(&self, hiker: &'a Hiker) -> &'a Skill {
+		match self.discipline {
+			Discipline::Hiking => &hiker.hiking,
+			Discipline::Swimming => &hiker.swimming,
+		}
+	}
+}
+
+/// A kind of terrain that is not built in.
+///
+/// A kind's travel time is its fixed time plus the trail's distance times a rate that depends on
+/// the hiker's skill in the kind's [`Discipline`]. A ferry, for example, takes a while to board
+/// whatever the length of the crossing. Built-in terrains have no fixed time.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct TerrainKind {
+	/// The name of the terrain, as used in the text encoding of trails.
+	pub name: String,
+	/// Who can traverse the terrain, and which skill sets their pace.
+	pub rules: TerrainRules,
+	/// The travel time multipliers for Beginner, Intermediate and Expert hikers, in that order.
+	/// These are used by every [`super::TerrainProfile`] that does not set its own.
+	pub rates: [Rate; 3],
+	/// The time added to every traversal, whatever its distance.
+	pub fixed_time: u32,
+}
+
+impl TerrainKind {
+	/// Returns whether `name` can be used for a terrain kind, so that trails and terrain profile
+	/// lines using it parse back unchanged.
+	///
+	/// Such names are not empty, have no surrounding whitespace and no line breaks, contain none of
+	/// `(`, `)`, `[`, `]` and `:`, and do not start with `#`.
+	pub fn is_valid_name(name: &str) -> bool {
+		!name.is_empty() &&
+			name.trim() == name &&
+			!name.contains(['\n', '\r', '(', ')', '[', ']', ':']) &&
+			!name.starts_with('#')
+	}
+}
+
+/// The reasons registering a terrain kind may fail.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RegisterError {
+	/// The name would not parse back unchanged, see [`TerrainKind::is_valid_name`].
+	InvalidName { name: String },
+	/// A different terrain, possibly a built-in one, already has the name.
+	Duplicate { name: String },
+}
+
+impl fmt::Display for RegisterError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RegisterError::InvalidName { name } =>
+				write!(f, "terrain name {name:?} would not parse back unchanged"),
+			RegisterError::Duplicate { name } =>
+				write!(f, "a different terrain named `{name}` already exists"),
+		}
+	}
+}
+
+impl std::error::Error for RegisterError {}
+
+/// Every registered terrain kind. They are leaked so that [`Terrain`] can stay `Copy`, which is
+/// fine as kinds are registered once and never removed.
+static REGISTRY: Mutex<Vec<&'static TerrainKind>> = Mutex::new(Vec::new());
+
+/// The terrains whose names cannot be registered.
+const BUILT_IN: [Terrain; 6] = [
+	Terrain::PavedTrail,
+	Terrain::UnpavedTrail,
+	Terrain::RockyTrail,
+	Terrain::Water,
+	Terrain::Zipline,
+	Terrain::RopeBridge,
+];
+
+impl Terrain {
+	/// Registers a new kind of terrain, returning the terrain to use for it.
+	///
+	/// Registering exactly the same kind again returns the same terrain. Registering a different
+	/// kind under a name that is taken, including the name of a built-in terrain, is an error, as
+	/// is a name that would not parse back.
+	pub fn register(kind: TerrainKind) -> Result<Terrain, RegisterError> {
+		if !TerrainKind::is_valid_name(&kind.name) {
+			return Err(RegisterError::InvalidName { name: kind.name })
+		}
+		if BUILT_IN.iter().any(|terrain| terrain.to_string() == kind.name) {
+			return Err(RegisterError::Duplicate { name: kind.name })
+		}
+
+		let mut registry = REGISTRY.lock().expect("terrain registry is never poisoned");
+		if let Some(existing) = registry.iter().find(|existing| existing.name == kind.name) {
+			return if **existing == kind {
+				Ok(Terrain::Custom(existing))
+			} else {
+				Err(RegisterError::Duplicate { name: kind.name })
+			}
+		}
+		let kind: &'static TerrainKind = Box::leak(Box::new(kind));
+		registry.push(kind);
+		Ok(Terrain::Custom(kind))
+	}
+
+	/// Returns the registered terrain kind with the given name, if there is one.
+	pub(crate) fn registered(name: &str) -> Option<Terrain> {
+		let registry = REGISTRY.lock().expect("terrain registry is never poisoned");
+		registry.iter().find(|kind| kind.name == name).map(|kind| Terrain::Custom(kind))
+	}
+
+	/// Returns who can traverse this terrain, and which skill sets their pace.
+	pub fn rules(&self) -> TerrainRules {
+		match self {
+			Terrain::Water =>
+				TerrainRules { discipline: Discipline::Swimming, ..TerrainRules::HIKING },
+			Terrain::RopeBridge => TerrainRules { requires_brave: true, ..TerrainRules::HIKING },
+			Terrain::Zipline =>
+				TerrainRules { requires_brave: true, requires_strong: true, ..TerrainRules::HIKING },
+			Terrain::PavedTrail | Terrain::UnpavedTrail | Terrain::RockyTrail =>
+				TerrainRules::HIKING,
+			Terrain::Custom(kind) => kind.rules,
+		}
+	}
+
+	/// Returns the time added to every traversal of this terrain, whatever its distance.
+	pub fn fixed_time(&self) -> u32 {
+		match self {
+			Terrain::Custom(kind) => kind.fixed_time,
+			_ => 0,
+		}
+	}
+}
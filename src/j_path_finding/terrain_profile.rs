@@ -0,0 +1,217 @@
+//! Configurable speed tables, for parks and seasons that differ from the standard one.
+//!
+//! A [`TerrainProfile`] gives, for every terrain, the multiplier applied to a trail's distance to
+//! get a hiker's travel time, depending on their skill. Which skill that is, and who may traverse
+//! the terrain at all, is up to the terrain's [`super::TerrainRules`].
+//!
+//! Profiles can be loaded from a simple text format, with one terrain per line, followed by either
+//! a single rate for every skill level, or one rate for each of Beginner, Intermediate and Expert.
+//! Rates are whole numbers or fractions. Blank lines and lines starting with `#` are skipped.
+//!
+//! ### Example:
+//! ```text
+//! # Winter: the water is cold and the rocks are icy
+//! Water: 12 8 4
+//! RockyTrail: 6 3 2
+//! Zipline: 1/10
+//! ```
+
+use super::{loader::load_lines, Hiker, LoadError, ParseError, Skill, Terrain};
+use std::{collections::HashMap, fmt, io::BufRead, str::FromStr, sync::OnceLock};
+
+/// A travel time multiplier, as a fraction. Travel times are rounded down.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rate {
+	pub numerator: u32,
+	pub denominator: u32,
+}
+
+impl Rate {
+	/// A rate of `numerator / denominator`.
+	pub const fn new(numerator: u32, denominator: u32) -> Self {
+		Self { numerator, denominator }
+	}
+
+	/// A whole number rate.
+	pub const fn times(multiplier: u32) -> Self {
+		Self::new(multiplier, 1)
+	}
+
+	/// Applies the rate to a distance, returning `None` if the result does not fit in a `u32`.
+	pub fn apply(&self, distance: u32) -> Option<u32> {
+		u32::try_from(self.apply_u64(distance)?).ok()
+	}
+
+	/// Applies the rate to a distance, without the risk of overflowing a `u32`. Returns `None`
+	/// only if the denominator is zero.
+	pub fn apply_u64(&self, distance: u32) -> Option<u64> {
+		// The product of two `u32`s always fits in a `u64`
+		(distance as u64 * self.numerator as u64).checked_div(self.denominator as u64)
+	}
+}
+
+impl fmt::Display for Rate {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.denominator {
+			1 => write!(f, "{}", self.numerator),
+			denominator => write!(f, "{}/{denominator}", self.numerator),
+		}
+	}
+}
+
+impl TryFrom<String> for Rate {
+	type Error = ParseError;
+
+	fn try_from(s: String) -> Result<Rate, ParseError> {
+		let invalid = || ParseError::InvalidRate { offset: 0, value: s.clone() };
+		let (numerator, denominator) = s.split_once('/').unwrap_or((&s, "1"));
+		let numerator = numerator.parse().map_err(|_| invalid())?;
+		let denominator = denominator.parse().map_err(|_| invalid())?;
+		if denominator == 0 {
+			return Err(invalid())
+		}
+		Ok(Rate { numerator, denominator })
+	}
+}
+
+/// The travel time multipliers for every terrain, by skill level.
+///
+/// Registered terrain kinds missing from a profile use their own rates. Built-in terrains missing
+/// from a profile cannot be traversed by anyone.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TerrainProfile {
+	/// The rates for each terrain, for Beginner, Intermediate and Expert hikers, in that order.
+	rates: HashMap<Terrain, [Rate; 3]>,
+}
+
+impl TerrainProfile {
+	/// The standard profile, used by [`Hiker::travel_time`].
+	pub fn standard() -> &'static TerrainProfile {
+		static STANDARD: OnceLock<TerrainProfile> = OnceLock::new();
+		STANDARD.get_or_init(|| {
+			let mut profile = TerrainProfile::default();
+			let half = Rate::new(1, 2);
+			let quarter = Rate::new(1, 4);
+			profile.set(Terrain::PavedTrail, [Rate::times(1), half, quarter]);
+			profile.set(Terrain::UnpavedTrail, [Rate::times(2), Rate::times(1), half]);
+			profile.set(Terrain::RockyTrail, [Rate::times(4), Rate::times(2), Rate::times(1)]);
+			profile.set(Terrain::Water, [Rate::times(9), Rate::times(6), Rate::times(3)]);
+			profile.set(Terrain::Zipline, [Rate::new(1, 10); 3]);
+			profile.set(Terrain::RopeBridge, [Rate::times(1); 3]);
+			profile
+		})
+	}
+
+	/// Sets the rates for a terrain, for Beginner, Intermediate and Expert hikers, in that order.
+	pub fn set(&mut self, terrain: Terrain, rates: [Rate; 3]) {
+		self.rates.insert(terrain, rates);
+	}
+
+	/// Returns the rate at which a hiker of the given skill traverses a terrain, if they can.
+	pub fn rate(&self, terrain: &Terrain, skill: &Skill) -> Option<Rate> {
+		let index = match skill {
+			Skill::Beginner => 0,
+			Skill::Intermediate => 1,
+			Skill::Expert => 2,
+		};
+		match (self.rates.get(terrain), terrain) {
+			(Some(rates), _) => Some(rates[index]),
+			(None, Terrain::Custom(kind)) => Some(kind.rates[index]),
+			(None, _) => None,
+		}
+	}
+
+	/// Loads a profile from any buffered reader, one terrain per line.
+	///
+	/// As for trail networks, every malformed line is reported together.
+	pub fn from_reader(reader: impl BufRead) -> Result<TerrainProfile, LoadError> {
+		let mut profile = TerrainProfile::default();
+		load_lines(reader, |line| {
+			let (terrain, rates) = parse_line(line)?;
+			profile.set(terrain, rates);
+			Ok(())
+		})?;
+		Ok(profile)
+	}
+}
+
+/// Parses a single line of a profile into the terrain and rates it describes.
+fn parse_line(line: &str) -> Result<(Terrain, [Rate; 3]), ParseError> {
+	let colon = line
+		.find(':')
+		.ok_or(ParseError::MissingDelimiter { offset: line.len(), delimiter: ":" })?;
+	let terrain_offset = line.len() - line.trim_start().len();
+	let terrain = Terrain::try_from(line[..colon].trim().to_string())
+		.map_err(|error| error.offset_by(terrain_offset))?;
+
+	// Keep a running offset over the pieces so each rate's errors point at the rate itself
+	let rates = line[colon + 1..]
+		.split_inclusive(char::is_whitespace)
+		.scan(colon + 1, |offset, piece| {
+			let start = *offset;
+			*offset += piece.len();
+			Some((start, piece.trim_end()))
+		})
+		.filter(|(_, rate)| !rate.is_empty())
+		.map(|(offset, rate)| {
+			Rate::try_from(rate.to_string()).map_err(|error| error.offset_by(offset))
+		})
+		.collect::<Result<Vec<Rate>, ParseError>>()?;
+
+	match rates[..] {
+		[rate] => Ok((terrain, [rate; 3])),
+		[beginner, intermediate, expert] => Ok((terrain, [beginner, intermediate, expert])),
+		_ => Err(ParseError::WrongRateCount { offset: colon + 1, count: rates.len() }),
+	}
+}
+
+impl FromStr for TerrainProfile {
+	type Err = LoadError;
+
+	/// Loads a profile from a multi-line document, one terrain per line.
+	fn from_str(document: &str) -> Result<Self, Self::Err> {
+		TerrainProfile::from_reader(document.as_bytes())
+	}
+}
+
+impl fmt::Display for TerrainProfile {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		// Sort the terrains so the output is stable
+		let mut terrains: Vec<(String, &[Rate; 3])> =
+			self.rates.iter().map(|(terrain, rates)| (terrain.to_string(), rates)).collect();
+		terrains.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+		terrains
+			.into_iter()
+			.try_for_each(|(terrain, [beginner, intermediate, expert])| {
+				writeln!(f, "{terrain}: {beginner} {intermediate} {expert}")
+			})
+	}
+}
+
+impl Hiker {
+	/// Like [`Hiker::travel_time`], but with the rates from the given profile.
+	pub fn travel_time_with(
+		&self,
+		profile: &TerrainProfile,
+		terrain: &Terrain,
+		distance: u32,
+	) -> Option<u32> {
+		u32::try_from(self.travel_time_with_u64(profile, terrain, distance)?).ok()
+	}
+
+	/// Like [`Hiker::travel_time_u64`], but with the rates from the given profile.
+	pub fn travel_time_with_u64(
+		&self,
+		profile: &TerrainProfile,
+		terrain: &Terrain,
+		distance: u32,
+	) -> Option<u64> {
+		let rules = terrain.rules();
+		if !rules.allows(self) {
+			return None
+		}
+		let rate = profile.rate(terrain, rules.skill(self))?;
+		rate.apply_u64(distance)?.checked_add(terrain.fixed_time() as u64)
+	}
+}
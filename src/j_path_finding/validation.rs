@@ -0,0 +1,173 @@
+//! Checks for the mistakes commonly found in hand-written trail networks, to run before a map is
+//! published.
+
+use super::{Terrain, Trail, TrailNetwork};
+use std::{collections::HashMap, fmt};
+
+/// How serious a problem is.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Severity {
+	/// Something that is often a mistake, but may be intended.
+	Warning,
+	/// Something that is almost certainly a mistake.
+	Error,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Severity::Warning => "warning",
+			Severity::Error => "error",
+		})
+	}
+}
+
+/// A problem found in a trail network.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Problem {
+	/// Campsites whose names only differ in whitespace or case, in the order they were added.
+	SimilarNames { names: Vec<String> },
+	/// The same trail appears more than once.
+	DuplicateTrail { trail: Trail, count: usize },
+	/// A campsite that no trail leaves, so a hiker who gets there is stuck.
+	DeadEnd { campsite: String },
+	/// A campsite that no trail arrives at, so it can only be a starting point. Campsites that can
+	/// only be reached from a disconnected part of the network are not reported.
+	NoIncomingTrail { campsite: String },
+	/// An ordinary hiking trail with no trail back between the same campsites. Ziplines, water
+	/// and rope bridges are often one-way on purpose, so they are not reported.
+	OneWay { trail: Trail },
+}
+
+impl Problem {
+	/// How serious the problem is.
+	pub fn severity(&self) -> Severity {
+		match self {
+			Problem::SimilarNames { .. } | Problem::DuplicateTrail { .. } => Severity::Error,
+			Problem::DeadEnd { .. } | Problem::NoIncomingTrail { .. } | Problem::OneWay { .. } =>
+				Severity::Warning,
+		}
+	}
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Problem::SimilarNames { names } => {
+				let names: Vec<String> = names.iter().map(|name| format!("{name:?}")).collect();
+				write!(f, "campsite names differ only in whitespace or case: {}", names.join(", "))
+			},
+			Problem::DuplicateTrail { trail, count } =>
+				write!(f, "trail appears {count} times: {}", described(trail)),
+			Problem::DeadEnd { campsite } => write!(f, "no trail leaves {campsite:?}"),
+			Problem::NoIncomingTrail { campsite } => write!(f, "no trail leads to {campsite:?}"),
+			Problem::OneWay { trail } => write!(f, "trail has no way back: {}", described(trail)),
+		}
+	}
+}
+
+/// Describes a trail for a report, quoting its campsite names so that stray whitespace shows.
+pub(crate) fn described(trail: &Trail) -> String {
+	let Trail { start, end, distance, terrain, danger } = trail;
+	format!("{start:?} => {end:?}: {distance} ({terrain}) [{danger}]")
+}
+
+/// Every problem found in a trail network, most serious first.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ValidationReport {
+	pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+	/// Returns whether no problems were found at all.
+	pub fn is_clean(&self) -> bool {
+		self.problems.is_empty()
+	}
+
+	/// Returns whether any problem is an error, rather than just a warning.
+	pub fn has_errors(&self) -> bool {
+		self.problems.iter().any(|problem| problem.severity() == Severity::Error)
+	}
+}
+
+impl fmt::Display for ValidationReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.problems
+			.iter()
+			.try_for_each(|problem| writeln!(f, "{}: {problem}", problem.severity()))
+	}
+}
+
+/// The form of a campsite name that similar names share: lowercase, with every run of whitespace
+/// collapsed to a single space.
+fn normalized(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+impl TrailNetwork {
+	/// Checks the network for likely mistakes, such as misspelled campsites or duplicate trails.
+	pub fn validate(&self) -> ValidationReport {
+		let mut problems = Vec::new();
+		let trails = self.trails();
+
+		// Group the campsites by normalized name, keeping groups in order of first appearance
+		let mut groups: Vec<Vec<String>> = Vec::new();
+		let mut group_of: HashMap<String, usize> = HashMap::new();
+		for name in self.campsites() {
+			let group = *group_of.entry(normalized(name)).or_insert_with(|| {
+				groups.push(Vec::new());
+				groups.len() - 1
+			});
+			groups[group].push(name.to_string());
+		}
+		problems.extend(
+			groups
+				.into_iter()
+				.filter(|names| names.len() > 1)
+				.map(|names| Problem::SimilarNames { names }),
+		);
+
+		// Equal trails leave the same campsite, so only those need comparing. Each duplicate is
+		// reported once, where it first appears.
+		for campsite in 0..self.campsite_count() {
+			let leaving = self.outgoing(campsite);
+			for (index, &id) in leaving.iter().enumerate() {
+				let trail = self.trail(id);
+				if leaving[..index].iter().any(|&other| self.trail(other) == trail) {
+					continue
+				}
+				let count =
+					leaving[index..].iter().filter(|&&other| self.trail(other) == trail).count();
+				if count > 1 {
+					problems.push(Problem::DuplicateTrail { trail: trail.clone(), count });
+				}
+			}
+		}
+
+		for campsite in 0..self.campsite_count() {
+			let name = self.campsite_name(campsite).to_string();
+			if self.outgoing(campsite).is_empty() {
+				problems.push(Problem::DeadEnd { campsite: name.clone() });
+			}
+			if self.incoming(campsite).is_empty() {
+				problems.push(Problem::NoIncomingTrail { campsite: name });
+			}
+		}
+
+		for (id, trail) in trails.iter().enumerate() {
+			let hiking = matches!(
+				trail.terrain,
+				Terrain::PavedTrail | Terrain::UnpavedTrail | Terrain::RockyTrail
+			);
+			let (start, end) = self.endpoints(id);
+			let returns = self.outgoing(end).iter().any(|&back| self.endpoints(back).1 == start);
+			if hiking && start != end && !returns {
+				problems.push(Problem::OneWay { trail: trail.clone() });
+			}
+		}
+
+		// The sort is stable, so problems of the same severity stay in the order found
+		problems.sort_by_key(|problem| std::cmp::Reverse(problem.severity()));
+		ValidationReport { problems }
+	}
+}
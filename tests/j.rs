@@ -8,7 +8,10 @@ fn terrain_from_string_1() {
 #[test]
 fn terrain_from_string_2() {
 	// Notice to incorrect casing (capital P)
-	assert_eq!(Terrain::try_from("UnPavedTrail".to_string()), Err(()))
+	assert_eq!(
+		Terrain::try_from("UnPavedTrail".to_string()),
+		Err(ParseError::UnknownTerrain { offset: 0, name: "UnPavedTrail".into() })
+	)
 }
 
 #[test]
@@ -18,7 +21,10 @@ fn skill_from_string_1() {
 
 #[test]
 fn skill_from_string_2() {
-	assert_eq!(Skill::try_from("Bogus".to_string()), Err(()))
+	assert_eq!(
+		Skill::try_from("Bogus".to_string()),
+		Err(ParseError::UnknownSkill { offset: 0, name: "Bogus".into() })
+	)
 }
 
 #[test]
@@ -161,3 +167,1670 @@ fn optimal_path_2() {
 
 	assert_eq!(op, Some(70), "OPTIONAL");
 }
+
+#[test]
+fn optimal_route_1() {
+	let route = optimal_route(
+		"Green Lake".into(),
+		"Prairie Meadow".into(),
+		&Default::default(),
+		test_path(),
+		|_, t| Some(t.danger as u32),
+	)
+	.unwrap();
+
+	assert_eq!(route.cost, 70);
+	assert_eq!(route.campsites, vec!["Green Lake", "B", "Prairie Meadow"]);
+	assert_eq!(
+		route.trails.iter().map(|t| t.terrain).collect::<Vec<_>>(),
+		vec![Terrain::PavedTrail, Terrain::UnpavedTrail]
+	);
+}
+
+#[test]
+fn optimal_route_2() {
+	// Bill is not brave, so he can never take the zipline and must go through B.
+	let route = optimal_route(
+		"Green Lake".into(),
+		"Prairie Meadow".into(),
+		&Default::default(),
+		test_path(),
+		|h, t| h.travel_time(&t.terrain, t.distance),
+	)
+	.unwrap();
+
+	assert_eq!(route.cost, 1200);
+	assert_eq!(route.campsites, vec!["Green Lake", "B", "Prairie Meadow"]);
+}
+
+#[test]
+fn optimal_route_unreachable() {
+	let route = optimal_route(
+		"Prairie Meadow".into(),
+		"Green Lake".into(),
+		&Default::default(),
+		test_path(),
+		|_, t| Some(t.distance),
+	);
+
+	assert_eq!(route, None);
+}
+
+#[test]
+fn trail_network_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let bill = Hiker::default();
+
+	assert_eq!(network.campsite_count(), 4);
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &bill, |_, t| Some(t.distance)),
+		Some(1400)
+	);
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &bill, |_, t| Some(t.danger as u32)),
+		Some(70)
+	);
+	assert_eq!(network.optimal_path("B", "A", &bill, |_, t| Some(t.distance)), None);
+	assert_eq!(network.optimal_path("Nowhere", "Nowhere", &bill, |_, t| Some(t.distance)), Some(0));
+}
+
+#[test]
+fn trail_network_2() {
+	let mut network = TrailNetwork::from_iter(test_path());
+	let hiker = Hiker { hiking: Skill::Expert, swimming: Skill::Expert, strong: true, brave: true };
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &hiker, travel_time),
+		Some(600)
+	);
+
+	// Without the paved trail, a brave and strong hiker takes the zipline instead.
+	let paved = network
+		.trails()
+		.iter()
+		.find(|t| t.terrain == Terrain::PavedTrail)
+		.unwrap()
+		.clone();
+	assert!(network.remove_trail(&paved));
+	assert!(!network.remove_trail(&paved));
+	assert_eq!(network.trails().len(), 3);
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &hiker, travel_time),
+		Some(1040)
+	);
+
+	network.add_trail(Trail {
+		start: "Green Lake".into(),
+		end: "Prairie Meadow".into(),
+		distance: 100,
+		terrain: Terrain::Water,
+		danger: 5,
+	});
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &hiker, travel_time),
+		Some(300)
+	);
+	assert!(network.contains_campsite("B"));
+}
+
+#[test]
+fn trail_network_from_str() {
+	let network: TrailNetwork = "
+		# Two ways to the meadow
+		Green Lake => A: 1000 (RockyTrail) [60]
+		A => Prairie Meadow: 400 (Zipline) [220]
+
+		Green Lake <=> B: 1000 (PavedTrail) [40]
+		B => Prairie Meadow: 700 (UnpavedTrail) [30]
+	"
+	.parse()
+	.unwrap();
+
+	assert_eq!(network.trails().len(), 5);
+	assert_eq!(
+		network.optimal_path("B", "Green Lake", &Hiker::default(), |_, t| Some(t.distance)),
+		Some(1000)
+	);
+	assert_eq!(
+		network.optimal_path("Green Lake", "Prairie Meadow", &Hiker::default(), |_, t| Some(
+			t.distance
+		)),
+		Some(1400)
+	);
+}
+
+#[test]
+fn trail_network_malformed_lines() {
+	let document = "Green Lake => A: 1000 (RockyTrail) [60]\n\
+		A => Prairie Meadow: far (Zipline) [220]\n\
+		# fine\n\
+		B => Prairie Meadow: 700 (Unpaved) [30]\n";
+
+	match TrailNetwork::from_reader(document.as_bytes()) {
+		Err(LoadError::Malformed(lines)) => {
+			assert_eq!(lines.iter().map(|l| l.line).collect::<Vec<_>>(), vec![2, 4]);
+			assert_eq!(
+				lines[1].error,
+				ParseError::UnknownTerrain { offset: 26, name: "Unpaved".into() }
+			);
+		},
+		other => panic!("expected malformed lines, got {other:?}"),
+	}
+}
+
+#[test]
+fn trail_from_string_errors() {
+	let parse = |s: &str| Trail::try_from(s.to_string());
+
+	assert_eq!(
+		parse("Mountain Top -> Green Lake: 2000 (PavedTrail) [19]"),
+		Err(ParseError::MissingDelimiter { offset: 50, delimiter: "=>" })
+	);
+	assert_eq!(
+		parse("Mountain Top => Green Lake: 2k (PavedTrail) [19]"),
+		Err(ParseError::InvalidDistance { offset: 28, value: "2k".into() })
+	);
+	assert_eq!(
+		parse("Mountain Top => Green Lake: 2000 (Paved) [19]"),
+		Err(ParseError::UnknownTerrain { offset: 34, name: "Paved".into() })
+	);
+	assert_eq!(
+		parse("Mountain Top => Green Lake: 2000 (PavedTrail) [256]"),
+		Err(ParseError::DangerOutOfRange { offset: 47, value: "256".into() })
+	);
+	assert_eq!(
+		parse("=> Green Lake: 2000 (PavedTrail) [19]"),
+		Err(ParseError::EmptyCampsite { offset: 0 })
+	);
+}
+
+#[test]
+fn hiker_from_string_errors() {
+	let parse = |s: &str| Hiker::try_from(s.to_string());
+
+	assert_eq!(
+		parse("swimming: Intermediate, hiking: Beginner, strong: false, brave: false"),
+		Err(ParseError::UnexpectedKey { offset: 0, expected: "hiking", found: "swimming".into() })
+	);
+	assert_eq!(
+		parse("hiking: Beginner, swimming: Intermediate, strong: no, brave: false"),
+		Err(ParseError::InvalidBool { offset: 50, value: "no".into() })
+	);
+	assert_eq!(
+		parse("hiking: Beginner, swimming: Intermediate"),
+		Err(ParseError::MissingKey { offset: 40, expected: "strong" })
+	);
+
+	let error = parse("hiking: Novice, swimming: Intermediate, strong: false, brave: false");
+	assert_eq!(error, Err(ParseError::UnknownSkill { offset: 8, name: "Novice".into() }));
+	assert_eq!(error.unwrap_err().to_string(), "unknown skill `Novice` at byte 8");
+}
+
+/// A small xorshift generator, so the property-style tests below are reproducible without any
+/// external crates.
+struct Rng(u64);
+
+impl Rng {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn below(&mut self, n: u64) -> u64 {
+		self.next() % n
+	}
+
+	fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+		items[self.below(items.len() as u64) as usize]
+	}
+
+	fn campsite(&mut self) -> String {
+		let words = ["Green", "Lake", "Prairie", "Meadow", "Mountain", "Top", "Bird", "Watch"];
+		let name = (0..=self.below(2)).map(|_| self.pick(&words)).collect::<Vec<_>>().join(" ");
+
+		// Now and then, a name that cannot be written in the text encoding
+		match self.below(16) {
+			0 => format!("{name}:Camp"),
+			1 => format!("{name} => Camp"),
+			2 => format!(" {name}"),
+			3 => format!("#{name}"),
+			_ => name,
+		}
+	}
+
+	fn terrain(&mut self) -> Terrain {
+		self.pick(&[
+			Terrain::PavedTrail,
+			Terrain::UnpavedTrail,
+			Terrain::RockyTrail,
+			Terrain::Water,
+			Terrain::Zipline,
+			Terrain::RopeBridge,
+		])
+	}
+
+	fn skill(&mut self) -> Skill {
+		match self.below(3) {
+			0 => Skill::Beginner,
+			1 => Skill::Intermediate,
+			_ => Skill::Expert,
+		}
+	}
+
+	fn trail(&mut self) -> Trail {
+		Trail {
+			start: self.campsite(),
+			end: self.campsite(),
+			distance: self.next() as u32,
+			terrain: self.terrain(),
+			danger: self.next() as u8,
+		}
+	}
+
+	/// A trail between the given campsites, at least `min_distance` long.
+	fn trail_between(&mut self, start: &str, end: &str, min_distance: u32) -> Trail {
+		Trail {
+			start: start.into(),
+			end: end.into(),
+			distance: min_distance + self.below(min_distance as u64 / 2 + 1) as u32,
+			terrain: self.terrain(),
+			danger: self.next() as u8,
+		}
+	}
+
+	fn hiker(&mut self) -> Hiker {
+		Hiker {
+			hiking: self.skill(),
+			swimming: self.skill(),
+			strong: self.below(2) == 0,
+			brave: self.below(2) == 0,
+		}
+	}
+}
+
+#[test]
+fn trail_to_string() {
+	let trail = Trail::try_from("Mountain Top => Green Lake: 2000 (PavedTrail) [19]".to_string());
+	assert_eq!(trail.unwrap().to_string(), "Mountain Top => Green Lake: 2000 (PavedTrail) [19]");
+}
+
+#[test]
+fn unencodable_trail_names() {
+	let trail = Trail::try_from("A => B: 1 (PavedTrail) [0]".to_string()).unwrap();
+	assert!(trail.is_encodable());
+	for end in ["B:C", "B => C", " B", "B\n", ""] {
+		let trail = Trail { end: end.into(), ..trail.clone() };
+		assert!(!trail.is_encodable(), "{end:?}");
+		assert_eq!(trail.encode(), Err(UnencodableCampsite { name: end.into() }));
+		assert_eq!(trail.to_string(), format!("A => {end}: 1 (PavedTrail) [0]"));
+	}
+	assert!(!Trail { start: "#A".into(), ..trail }.is_encodable());
+}
+
+#[test]
+fn hiker_to_string() {
+	assert_eq!(
+		Hiker::default().to_string(),
+		"hiking: Intermediate, swimming: Expert, strong: true, brave: false"
+	);
+}
+
+#[test]
+fn trail_round_trip() {
+	let mut rng = Rng(0x5eed);
+	let mut unencodable = 0;
+	for _ in 0..1000 {
+		let trail = rng.trail();
+		if trail.is_encodable() {
+			assert_eq!(Trail::try_from(trail.encode().unwrap()), Ok(trail));
+		} else {
+			unencodable += 1;
+			assert!(trail.encode().is_err());
+		}
+	}
+	assert!(unencodable > 0);
+}
+
+#[test]
+fn hiker_round_trip() {
+	let mut rng = Rng(0x5eed);
+	for _ in 0..100 {
+		let hiker = rng.hiker();
+		assert_eq!(Hiker::try_from(hiker.to_string()), Ok(hiker));
+	}
+}
+
+#[test]
+fn terrain_and_skill_round_trip() {
+	let mut rng = Rng(0x5eed);
+	for _ in 0..100 {
+		let terrain = rng.terrain();
+		assert_eq!(Terrain::try_from(terrain.to_string()), Ok(terrain));
+		let skill = rng.skill();
+		assert_eq!(Skill::try_from(skill.to_string()), Ok(skill));
+	}
+}
+
+#[test]
+fn trail_network_round_trip() {
+	let mut rng = Rng(0x5eed);
+	let network =
+		TrailNetwork::from_iter((0..100).map(|_| rng.trail()).filter(|trail| trail.is_encodable()));
+	let reloaded: TrailNetwork = network.encode().unwrap().parse().unwrap();
+	assert_eq!(reloaded.trails(), network.trails());
+	assert_eq!(network.encode().unwrap(), network.to_string());
+
+	let mut network = network;
+	network.add_trail(Trail::try_from("A => B: 1 (PavedTrail) [0]".to_string()).unwrap());
+	network.add_trail(Trail { start: "A\nB".into(), ..network.trails()[0].clone() });
+	assert_eq!(network.encode(), Err(UnencodableCampsite { name: "A\nB".into() }));
+	assert!(network.to_string().contains("A\nB => "));
+}
+
+#[test]
+fn tradeoff_routes_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let hiker = Hiker { hiking: Skill::Expert, swimming: Skill::Expert, strong: true, brave: true };
+	let routes = network.tradeoff_routes("Green Lake", "Prairie Meadow", &hiker);
+
+	// The zipline is shorter, but slower and far more dangerous.
+	assert_eq!(
+		routes.iter().map(|r| r.cost.clone()).collect::<Vec<_>>(),
+		vec![vec![1400, 1040, 280], vec![1700, 600, 70]]
+	);
+	assert_eq!(routes[0].campsites, vec!["Green Lake", "A", "Prairie Meadow"]);
+	assert_eq!(routes[1].campsites, vec!["Green Lake", "B", "Prairie Meadow"]);
+
+	// Bill can't take the zipline at all.
+	let routes = network.tradeoff_routes("Green Lake", "Prairie Meadow", &Hiker::default());
+	assert_eq!(routes.len(), 1);
+	assert_eq!(routes[0].cost, vec![1700, 1200, 70]);
+}
+
+#[test]
+fn pareto_routes_drops_dominated_routes() {
+	let mut network = TrailNetwork::from_iter(test_path());
+	// Strictly worse than going through B in every way.
+	network.add_trail(Trail {
+		start: "Green Lake".into(),
+		end: "Prairie Meadow".into(),
+		distance: 5000,
+		terrain: Terrain::RockyTrail,
+		danger: 100,
+	});
+
+	let routes = network.pareto_routes(
+		"Green Lake",
+		"Prairie Meadow",
+		&Hiker::default(),
+		&[&|_, t| Some(t.distance), &|_, t| Some(t.danger as u32)],
+	);
+	assert_eq!(
+		routes.iter().map(|r| r.cost.clone()).collect::<Vec<_>>(),
+		vec![vec![1400, 280], vec![1700, 70]]
+	);
+}
+
+#[test]
+fn constrained_route_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let hiker = Hiker { hiking: Skill::Expert, swimming: Skill::Expert, strong: true, brave: true };
+	let danger = |_: &Hiker, t: &Trail| Some(t.danger as u32);
+
+	// Shortest overall is over the zipline.
+	let route = network
+		.constrained_route("Green Lake", "Prairie Meadow", &hiker, |_, t| Some(t.distance), &[])
+		.unwrap();
+	assert_eq!(route.cost, 1400);
+
+	// No trail over 150 danger rules out the zipline.
+	let budget = Budget { resource: &danger, max_per_trail: Some(150), max_total: None };
+	let route = network
+		.constrained_route(
+			"Green Lake",
+			"Prairie Meadow",
+			&hiker,
+			|_, t| Some(t.distance),
+			&[budget],
+		)
+		.unwrap();
+	assert_eq!(route.cost, 1700);
+	assert_eq!(route.campsites, vec!["Green Lake", "B", "Prairie Meadow"]);
+
+	// A total danger budget too small for either route.
+	let budget = Budget { resource: &danger, max_per_trail: None, max_total: Some(69) };
+	let route = network.constrained_route(
+		"Green Lake",
+		"Prairie Meadow",
+		&hiker,
+		|_, t| Some(t.distance),
+		&[budget],
+	);
+	assert_eq!(route, None);
+}
+
+#[test]
+fn constrained_route_keeps_costlier_partial_routes() {
+	// The cheap way to C uses up most of the danger budget, so the route must take the expensive
+	// way to C to be able to afford the last trail.
+	let network: TrailNetwork = "
+		S => C: 1 (PavedTrail) [90]
+		S => C: 5 (PavedTrail) [10]
+		C => T: 1 (PavedTrail) [50]
+	"
+	.parse()
+	.unwrap();
+	let budget = Budget {
+		resource: &|_, t| Some(t.danger as u32),
+		max_per_trail: None,
+		max_total: Some(100),
+	};
+
+	let route = network
+		.constrained_route("S", "T", &Hiker::default(), |_, t| Some(t.distance), &[budget])
+		.unwrap();
+	assert_eq!(route.cost, 6);
+	assert_eq!(route.trails.iter().map(|t| t.danger).collect::<Vec<_>>(), vec![10, 50]);
+}
+
+#[test]
+fn k_shortest_routes_1() {
+	let network: TrailNetwork = "
+		C => D: 3 (PavedTrail) [0]
+		C => E: 2 (PavedTrail) [0]
+		E => D: 1 (PavedTrail) [0]
+		D => F: 4 (PavedTrail) [0]
+		E => F: 2 (PavedTrail) [0]
+		E => G: 3 (PavedTrail) [0]
+		F => G: 2 (PavedTrail) [0]
+		F => H: 1 (PavedTrail) [0]
+		G => H: 2 (PavedTrail) [0]
+	"
+	.parse()
+	.unwrap();
+
+	let routes = network.k_shortest_routes("C", "H", &Hiker::default(), 3, |_, t| Some(t.distance));
+	assert_eq!(routes.iter().map(|r| r.cost).collect::<Vec<_>>(), vec![5, 7, 8]);
+	assert_eq!(routes[0].campsites, vec!["C", "E", "F", "H"]);
+	assert_eq!(routes[1].campsites, vec!["C", "E", "G", "H"]);
+
+	let routes = network.k_shortest_routes("C", "H", &Hiker::default(), 0, |_, t| Some(t.distance));
+	assert!(routes.is_empty());
+
+	// Every route is loopless and distinct.
+	let routes =
+		network.k_shortest_routes("C", "H", &Hiker::default(), 100, |_, t| Some(t.distance));
+	assert!(routes.windows(2).all(|pair| pair[0].cost <= pair[1].cost));
+	for (i, route) in routes.iter().enumerate() {
+		let unique: std::collections::HashSet<_> = route.campsites.iter().collect();
+		assert_eq!(unique.len(), route.campsites.len());
+		assert!(routes[..i].iter().all(|other| other.trails != route.trails));
+	}
+}
+
+#[test]
+fn k_shortest_routes_respects_cost_function() {
+	// Bill can't take the zipline, so there is only one route for him.
+	let network = TrailNetwork::from_iter(test_path());
+	let routes =
+		network.k_shortest_routes("Green Lake", "Prairie Meadow", &Hiker::default(), 5, |h, t| {
+			h.travel_time(&t.terrain, t.distance)
+		});
+	assert_eq!(routes.len(), 1);
+	assert_eq!(routes[0].cost, 1200);
+}
+
+/// A square grid of campsites 100 meters apart, with a two-way trail between neighbors. Every
+/// trail is at least as long as the straight line between its ends.
+fn grid_network(size: u32, rng: &mut Rng) -> TrailNetwork {
+	let mut network = TrailNetwork::new();
+	for x in 0..size {
+		for y in 0..size {
+			let name = format!("{x},{y}");
+			network
+				.set_coordinates(&name, Coordinates { x: x as f64 * 100.0, y: y as f64 * 100.0 });
+			for neighbor in [(x + 1, y), (x, y + 1)] {
+				if neighbor.0 < size && neighbor.1 < size {
+					let neighbor = format!("{},{}", neighbor.0, neighbor.1);
+					network.add_trail(rng.trail_between(&name, &neighbor, 100));
+					network.add_trail(rng.trail_between(&neighbor, &name, 100));
+				}
+			}
+		}
+	}
+	network
+}
+
+#[test]
+fn astar_matches_dijkstra() {
+	let mut rng = Rng(0xa57a);
+	let network = grid_network(12, &mut rng);
+	let hiker =
+		Hiker { hiking: Skill::Expert, swimming: Skill::Beginner, strong: true, brave: true };
+	let heuristic = StraightLine { cost_per_meter: 1.0 };
+
+	for _ in 0..50 {
+		let start = format!("{},{}", rng.below(12), rng.below(12));
+		let destination = format!("{},{}", rng.below(12), rng.below(12));
+		let distance = |_: &Hiker, t: &Trail| Some(t.distance);
+		assert_eq!(
+			network
+				.astar_route(&start, &destination, &hiker, distance, &heuristic)
+				.map(|r| r.cost),
+			network.optimal_path(&start, &destination, &hiker, distance)
+		);
+
+		// Ziplines are ten times faster than walking their length.
+		let heuristic = StraightLine { cost_per_meter: 0.1 };
+		let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+		assert_eq!(
+			network
+				.astar_route(&start, &destination, &hiker, travel_time, &heuristic)
+				.map(|r| r.cost),
+			network.optimal_path(&start, &destination, &hiker, travel_time)
+		);
+	}
+}
+
+#[test]
+fn astar_expands_fewer_campsites() {
+	let mut rng = Rng(0xa57a);
+	let network = grid_network(30, &mut rng);
+	let without_coordinates = TrailNetwork::from_iter(network.trails().iter().cloned());
+	let distance = |_: &Hiker, t: &Trail| Some(t.distance);
+	let heuristic = StraightLine { cost_per_meter: 1.0 };
+
+	let guided = network.astar("0,0", "29,0", &Hiker::default(), distance, &heuristic);
+	let unguided =
+		without_coordinates.astar("0,0", "29,0", &Hiker::default(), distance, &heuristic);
+
+	assert_eq!(guided.route.map(|r| r.cost), unguided.route.map(|r| r.cost));
+	assert!(
+		guided.expanded * 2 < unguided.expanded,
+		"{} vs {}",
+		guided.expanded,
+		unguided.expanded
+	);
+}
+
+#[test]
+fn all_pairs_costs_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let matrix = network.all_pairs_costs(&Hiker::default(), |_, t| Some(t.distance));
+
+	assert_eq!(matrix.cost("Green Lake", "Prairie Meadow"), Some(1400));
+	assert_eq!(matrix.cost("B", "Prairie Meadow"), Some(700));
+	assert_eq!(matrix.cost("A", "A"), Some(0));
+	assert_eq!(matrix.cost("Prairie Meadow", "Green Lake"), None);
+	assert_eq!(matrix.cost("Nowhere", "A"), None);
+
+	assert_eq!(
+		matrix.to_csv(),
+		",Green Lake,A,Prairie Meadow,B\n\
+		Green Lake,0,1000,1400,1000\n\
+		A,,0,400,\n\
+		Prairie Meadow,,,0,\n\
+		B,,,700,0\n"
+	);
+}
+
+/// A network of random trails between the given number of campsites.
+fn random_network(rng: &mut Rng, campsites: u64, trails: usize) -> TrailNetwork {
+	TrailNetwork::from_iter((0..trails).map(|_| {
+		let (start, end) = (rng.below(campsites), rng.below(campsites));
+		rng.trail_between(&format!("Site {start}"), &format!("Site {end}"), 10)
+	}))
+}
+
+#[test]
+fn all_pairs_algorithms_agree() {
+	let mut rng = Rng(0xa11);
+	let network = random_network(&mut rng, 25, 80);
+	let hiker = rng.hiker();
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+
+	let floyd_warshall =
+		network.all_pairs_costs_using(AllPairsAlgorithm::FloydWarshall, &hiker, travel_time);
+	let dijkstra =
+		network.all_pairs_costs_using(AllPairsAlgorithm::RepeatedDijkstra, &hiker, travel_time);
+	assert_eq!(floyd_warshall, dijkstra);
+
+	for from in network.campsites() {
+		for to in network.campsites() {
+			assert_eq!(
+				dijkstra.cost(from, to),
+				network.optimal_path(from, to, &hiker, travel_time)
+			);
+		}
+	}
+}
+
+#[test]
+fn bills_safest_path_uses_its_endpoints() {
+	// Regression test: this used to always route from Green Lake to Prairie Meadows.
+	assert_eq!(bills_safest_path("A".into(), "Prairie Meadow".into(), test_path()), Some(220));
+	assert_eq!(bills_safest_path("Green Lake".into(), "B".into(), test_path()), Some(40));
+	assert_eq!(bills_safest_path("B".into(), "Green Lake".into(), test_path()), None);
+}
+
+#[test]
+fn fastest_path_uses_its_hiker() {
+	// Regression test: this used to ignore its hiker and always route for Bill.
+	let trails = || {
+		vec![
+			"Green Lake => Prairie Meadows: 1000 (Zipline) [200]",
+			"Green Lake => Prairie Meadows: 1000 (PavedTrail) [0]",
+		]
+		.into_iter()
+		.map(|line| Trail::try_from(line.to_string()).unwrap())
+	};
+	let daredevil =
+		Hiker { hiking: Skill::Expert, swimming: Skill::Expert, strong: true, brave: true };
+
+	assert_eq!(fastest_path_from_green_lake_to_prairie_meadows(&daredevil, trails()), Some(100));
+	assert_eq!(
+		fastest_path_from_green_lake_to_prairie_meadows(&Hiker::default(), trails()),
+		Some(500)
+	);
+}
+
+#[test]
+fn route_query_objectives() {
+	let network = TrailNetwork::from_iter(test_path());
+	let query = RouteQuery::new("Green Lake", "Prairie Meadow");
+
+	assert_eq!(query.clone().find_in(&network).map(|r| r.cost), Some(1400));
+	assert_eq!(
+		query.clone().objective(Objective::Danger).find(test_path()).map(|r| r.cost),
+		Some(70)
+	);
+	assert_eq!(
+		query.clone().objective(Objective::TravelTime).find_in(&network).map(|r| r.cost),
+		Some(1200)
+	);
+	assert_eq!(
+		query
+			.objective(Objective::Custom(&|_, t| Some(t.distance + t.danger as u32)))
+			.find_in(&network),
+		optimal_route(
+			"Green Lake".into(),
+			"Prairie Meadow".into(),
+			&Hiker::default(),
+			test_path(),
+			|_, t| { Some(t.distance + t.danger as u32) }
+		)
+	);
+}
+
+#[test]
+fn plan_itinerary_1() {
+	let network: TrailNetwork = "
+		S => A: 1 (PavedTrail) [0]
+		A <=> B: 1 (PavedTrail) [0]
+		B => T: 1 (PavedTrail) [0]
+		S => B: 5 (PavedTrail) [0]
+		A => T: 10 (PavedTrail) [0]
+	"
+	.parse()
+	.unwrap();
+	let distance = |_: &Hiker, t: &Trail| Some(t.distance);
+
+	let itinerary = network
+		.plan_itinerary("S", &["B", "A"], "T", WaypointOrder::Any, &Hiker::default(), distance)
+		.unwrap();
+	assert_eq!(itinerary.stops, vec!["S", "A", "B", "T"]);
+	assert_eq!(itinerary.leg_costs, vec![1, 1, 1]);
+	assert_eq!(itinerary.route.cost, 3);
+	assert_eq!(itinerary.route.campsites, vec!["S", "A", "B", "T"]);
+
+	let itinerary = network
+		.plan_itinerary("S", &["B", "A"], "T", WaypointOrder::Fixed, &Hiker::default(), distance)
+		.unwrap();
+	assert_eq!(itinerary.stops, vec!["S", "B", "A", "T"]);
+	assert_eq!(itinerary.leg_costs, vec![2, 1, 2]);
+	assert_eq!(itinerary.route.campsites, vec!["S", "A", "B", "A", "B", "T"]);
+
+	assert_eq!(
+		network.plan_itinerary("T", &["A"], "S", WaypointOrder::Any, &Hiker::default(), distance),
+		None
+	);
+}
+
+#[test]
+fn plan_itinerary_many_waypoints() {
+	let mut rng = Rng(0x17e);
+	let network = grid_network(8, &mut rng);
+	let distance = |_: &Hiker, t: &Trail| Some(t.distance);
+	let waypoints: Vec<String> = (0..EXACT_WAYPOINT_LIMIT + 3)
+		.map(|_| format!("{},{}", rng.below(8), rng.below(8)))
+		.collect();
+	let waypoints: Vec<&str> = waypoints.iter().map(String::as_str).collect();
+
+	for count in [EXACT_WAYPOINT_LIMIT, EXACT_WAYPOINT_LIMIT + 3] {
+		let any = network
+			.plan_itinerary(
+				"0,0",
+				&waypoints[..count],
+				"7,7",
+				WaypointOrder::Any,
+				&Hiker::default(),
+				distance,
+			)
+			.unwrap();
+		let fixed = network
+			.plan_itinerary(
+				"0,0",
+				&waypoints[..count],
+				"7,7",
+				WaypointOrder::Fixed,
+				&Hiker::default(),
+				distance,
+			)
+			.unwrap();
+
+		assert!(any.route.cost <= fixed.route.cost);
+		assert_eq!(any.route.cost, any.leg_costs.iter().sum::<u32>());
+		assert_eq!(any.route.trails.len() + 1, any.route.campsites.len());
+		assert!(waypoints[..count].iter().all(|w| any.stops.iter().any(|s| s == w)));
+	}
+}
+
+#[test]
+fn party_travel_time() {
+	let party = Party {
+		members: vec![
+			Hiker { hiking: Skill::Expert, swimming: Skill::Beginner, strong: true, brave: true },
+			Hiker { hiking: Skill::Beginner, swimming: Skill::Expert, strong: true, brave: true },
+		],
+	};
+	assert_eq!(party.travel_time(&Terrain::Water, 10), Some(90));
+	assert_eq!(party.travel_time(&Terrain::RockyTrail, 10), Some(40));
+	assert_eq!(party.travel_time(&Terrain::Zipline, 100), Some(10));
+
+	let party =
+		Party { members: vec![Hiker { brave: true, ..Hiker::default() }, Hiker::default()] };
+	assert_eq!(party.travel_time(&Terrain::Zipline, 100), None);
+	assert_eq!(Party::default().travel_time(&Terrain::PavedTrail, 100), None);
+}
+
+#[test]
+fn party_route_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let daredevil =
+		|| Hiker { hiking: Skill::Expert, swimming: Skill::Expert, strong: true, brave: true };
+
+	// On their own, daredevils take the zipline for the shortest route...
+	let party = Party { members: vec![daredevil(), daredevil()] };
+	let route = network.party_route("Green Lake", "Prairie Meadow", &party, Objective::Distance);
+	assert_eq!(route.map(|r| r.cost), Some(1400));
+
+	// ...but not when Bill comes along.
+	let party = Party { members: vec![daredevil(), Hiker::default()] };
+	let route = network.party_route("Green Lake", "Prairie Meadow", &party, Objective::Distance);
+	assert_eq!(route.map(|r| r.cost), Some(1700));
+
+	// The party moves at Bill's pace.
+	let route = network.fastest_party_route("Green Lake", "Prairie Meadow", &party).unwrap();
+	assert_eq!(route.cost, 1200);
+	assert_eq!(route.campsites, vec!["Green Lake", "B", "Prairie Meadow"]);
+
+	// A profile sets the pace, and Bill still can't take the zipline under any objective.
+	let trail = |s: &str| Trail::try_from(s.to_string()).unwrap();
+	let profile: TerrainProfile = "Water: 2".parse().unwrap();
+	let water = trail("A => B: 10 (Water) [0]");
+	let zipline = trail("A => B: 10 (Zipline) [0]");
+	assert_eq!(party.cost(&Objective::TravelTimeWith(&profile), &water), Some(20));
+	assert_eq!(party.cost(&Objective::TravelTimeWith(&profile), &zipline), None);
+	assert_eq!(party.cost(&Objective::Distance, &water), Some(10));
+	assert_eq!(party.cost(&Objective::Distance, &zipline), None);
+}
+
+#[test]
+fn standard_terrain_profile() {
+	// The standard profile must keep the rates `travel_time` always had.
+	let expected = |terrain: Terrain, skill: &Skill, distance: u32| {
+		let index = match skill {
+			Skill::Beginner => 0,
+			Skill::Intermediate => 1,
+			Skill::Expert => 2,
+		};
+		match terrain {
+			Terrain::PavedTrail => [distance, distance / 2, distance / 4][index],
+			Terrain::UnpavedTrail => [2 * distance, distance, distance / 2][index],
+			Terrain::RockyTrail => [4 * distance, 2 * distance, distance][index],
+			Terrain::Water => [9 * distance, 6 * distance, 3 * distance][index],
+			Terrain::Zipline => distance / 10,
+			Terrain::RopeBridge => distance,
+			Terrain::Custom(_) => unreachable!("only built-in terrains are generated"),
+		}
+	};
+
+	let mut rng = Rng(0xfeed);
+	for _ in 0..1000 {
+		let (hiker, terrain, distance) = (rng.hiker(), rng.terrain(), rng.below(100_000) as u32);
+		let skill = if terrain == Terrain::Water { &hiker.swimming } else { &hiker.hiking };
+		let allowed = match terrain {
+			Terrain::RopeBridge => hiker.brave,
+			Terrain::Zipline => hiker.brave && hiker.strong,
+			_ => true,
+		};
+		assert_eq!(
+			hiker.travel_time(&terrain, distance),
+			allowed.then(|| expected(terrain, skill, distance))
+		);
+	}
+}
+
+#[test]
+fn terrain_profile_from_str() {
+	let profile: TerrainProfile = "
+		# Winter: the water is cold and the rocks are icy
+		Water: 12 8 4
+		RockyTrail: 6 3 3/2
+		Zipline: 1/10
+	"
+	.parse()
+	.unwrap();
+
+	let hiker = Hiker::default();
+	assert_eq!(hiker.travel_time_with(&profile, &Terrain::Water, 10), Some(40));
+	assert_eq!(hiker.travel_time_with(&profile, &Terrain::RockyTrail, 10), Some(30));
+	assert_eq!(hiker.travel_time_with(&profile, &Terrain::PavedTrail, 10), None);
+	assert_eq!(hiker.travel_time_with(&profile, &Terrain::Zipline, 100), None);
+	assert_eq!(profile.rate(&Terrain::RockyTrail, &Skill::Expert), Some(Rate::new(3, 2)));
+
+	let reloaded: TerrainProfile = profile.to_string().parse().unwrap();
+	assert_eq!(reloaded, profile);
+	let reloaded: TerrainProfile = TerrainProfile::standard().to_string().parse().unwrap();
+	assert_eq!(&reloaded, TerrainProfile::standard());
+
+	// Routes can be planned with a profile too.
+	let route = RouteQuery::new("Green Lake", "Prairie Meadow")
+		.objective(Objective::TravelTimeWith(&profile))
+		.find(test_path());
+	assert_eq!(route, None);
+}
+
+#[test]
+fn terrain_profile_malformed_lines() {
+	// No test registers `Quicksand`, so it stays unknown however the tests are ordered.
+	let error = "Water: 12 8\nQuicksand: 3\nRockyTrail: 1/0\n"
+		.parse::<TerrainProfile>()
+		.unwrap_err();
+	assert!(error.to_string().starts_with("3 malformed line(s)\n  line 1: "));
+	match error {
+		LoadError::Malformed(lines) => {
+			assert_eq!(
+				lines.iter().map(|l| l.error.clone()).collect::<Vec<_>>(),
+				vec![
+					ParseError::WrongRateCount { offset: 6, count: 2 },
+					ParseError::UnknownTerrain { offset: 0, name: "Quicksand".into() },
+					ParseError::InvalidRate { offset: 12, value: "1/0".into() },
+				]
+			);
+		},
+		other => panic!("expected malformed lines, got {other:?}"),
+	}
+}
+
+/// A strong-only hiking terrain. The terrain registry is shared by every test in this binary, so
+/// each test registers it under its own name.
+fn snow_kind(name: &str) -> TerrainKind {
+	TerrainKind {
+		name: name.into(),
+		rules: TerrainRules {
+			requires_brave: false,
+			requires_strong: true,
+			discipline: Discipline::Hiking,
+		},
+		rates: [Rate::times(8), Rate::times(5), Rate::times(2)],
+		fixed_time: 0,
+	}
+}
+
+#[test]
+fn register_terrain() {
+	let snow = Terrain::register(snow_kind("Snow")).unwrap();
+	assert_eq!(Terrain::register(snow_kind("Snow")), Ok(snow));
+	assert_eq!(Terrain::try_from("Snow".to_string()), Ok(snow));
+	assert_eq!(snow.to_string(), "Snow");
+
+	assert_eq!(
+		Terrain::register(TerrainKind { rates: [Rate::times(1); 3], ..snow_kind("Snow") }),
+		Err(RegisterError::Duplicate { name: "Snow".into() })
+	);
+	assert_eq!(
+		Terrain::register(TerrainKind { name: "Water".into(), ..snow_kind("Snow") }),
+		Err(RegisterError::Duplicate { name: "Water".into() })
+	);
+
+	for name in
+		["", " Slush", "Slush ", "Slush (wet)", "Slush)", "Slush [1]", "Slush: wet", "#Slush"]
+	{
+		assert_eq!(
+			Terrain::register(snow_kind(name)),
+			Err(RegisterError::InvalidName { name: name.into() })
+		);
+		assert_eq!(Terrain::try_from(name.to_string()).ok(), None);
+	}
+}
+
+#[test]
+fn custom_terrain_travel_time() {
+	let snow = Terrain::register(snow_kind("Glacier")).unwrap();
+	let ferry = Terrain::register(TerrainKind {
+		name: "Ferry".into(),
+		rules: TerrainRules::HIKING,
+		rates: [Rate::new(1, 5); 3],
+		fixed_time: 30,
+	})
+	.unwrap();
+
+	assert_eq!(Hiker::default().travel_time(&snow, 10), Some(50));
+	assert_eq!(Hiker { strong: false, ..Hiker::default() }.travel_time(&snow, 10), None);
+	assert_eq!(Hiker::default().travel_time(&ferry, 1000), Some(230));
+	assert_eq!(Hiker::default().travel_time(&ferry, 0), Some(30));
+
+	// Profiles may override the rates of custom terrains.
+	let profile: TerrainProfile = "Glacier: 1".parse().unwrap();
+	assert_eq!(Hiker::default().travel_time_with(&profile, &snow, 10), Some(10));
+
+	// Trails parse with custom terrains, without any changes to the trail parser.
+	let network: TrailNetwork = "
+		Green Lake => Ferry Dock: 100 (Glacier) [10]
+		Ferry Dock => Prairie Meadow: 5000 (Ferry) [5]
+	"
+	.parse()
+	.unwrap();
+	let route = network.optimal_route("Green Lake", "Prairie Meadow", &Hiker::default(), |h, t| {
+		h.travel_time(&t.terrain, t.distance)
+	});
+	assert_eq!(route.map(|r| r.cost), Some(1530));
+}
+
+#[test]
+fn schedule_next_departure() {
+	let ferry = Schedule::repeating(
+		1440,
+		[Window { opens: 960, closes: 960 }, Window { opens: 480, closes: 480 }],
+	);
+	assert_eq!(ferry.next_departure(0), Some(480));
+	assert_eq!(ferry.next_departure(480), Some(480));
+	assert_eq!(ferry.next_departure(481), Some(960));
+	assert_eq!(ferry.next_departure(1000), Some(1920));
+
+	let bridge = Schedule::once([Window { opens: 100, closes: 200 }]);
+	assert_eq!(bridge.next_departure(150), Some(150));
+	assert_eq!(bridge.next_departure(201), None);
+	assert_eq!(Schedule::repeating(0, []).next_departure(10), None);
+}
+
+#[test]
+fn earliest_arrival_waits_for_ferry() {
+	let mut network: TrailNetwork = "
+		Green Lake => Ferry Dock: 30 (PavedTrail) [0]
+		Ferry Dock => Bird Island: 60 (Water) [0]
+		Green Lake => Bird Island: 600 (RockyTrail) [0]
+	"
+	.parse()
+	.unwrap();
+	let ferry = Trail::try_from("Ferry Dock => Bird Island: 60 (Water) [0]".to_string()).unwrap();
+	let departures = [Window { opens: 480, closes: 480 }, Window { opens: 960, closes: 960 }];
+	assert!(network.set_schedule(&ferry, Some(Schedule::repeating(1440, departures))));
+
+	let arrive = |departure| {
+		network
+			.earliest_arrival("Green Lake", "Bird Island", &Hiker::default(), departure, |_, t| {
+				Some(t.distance)
+			})
+			.unwrap()
+	};
+
+	// Waiting for the morning ferry still beats the long way round...
+	let journey = arrive(0);
+	assert_eq!(journey.arrival, 540);
+	assert_eq!(journey.departures, vec![0, 480]);
+	assert_eq!(journey.route.cost, 540);
+	assert_eq!(journey.route.campsites, vec!["Green Lake", "Ferry Dock", "Bird Island"]);
+
+	assert_eq!(arrive(500).arrival, 1020);
+	assert_eq!(arrive(900).departures, vec![900, 960]);
+
+	// ...but not waiting overnight.
+	let journey = arrive(1000);
+	assert_eq!(journey.arrival, 1600);
+	assert_eq!(journey.route.campsites, vec!["Green Lake", "Bird Island"]);
+}
+
+#[test]
+fn earliest_arrival_seasonal_closure() {
+	let mut network: TrailNetwork =
+		"Green Lake => Prairie Meadow: 100 (RopeBridge) [50]".parse().unwrap();
+	let bridge = network.trails()[0].clone();
+	network.set_schedule(&bridge, Some(Schedule::once([Window { opens: 0, closes: 10_000 }])));
+
+	let daredevil = Hiker { brave: true, ..Hiker::default() };
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+	let arrive = |network: &TrailNetwork, departure| {
+		network
+			.earliest_arrival("Green Lake", "Prairie Meadow", &daredevil, departure, travel_time)
+			.map(|journey| journey.arrival)
+	};
+	assert_eq!(arrive(&network, 10_000), Some(10_100));
+	assert_eq!(arrive(&network, 10_001), None);
+
+	// Removing the schedule opens the trail again.
+	assert!(network.set_schedule(&bridge, None));
+	assert_eq!(arrive(&network, 10_001), Some(10_101));
+	assert!(!network.set_schedule(&Trail { danger: 0, ..bridge }, None));
+}
+
+#[test]
+fn earliest_arrival_without_schedules_matches_dijkstra() {
+	let mut rng = Rng(0x71e);
+	let network = random_network(&mut rng, 30, 120);
+	let hiker = rng.hiker();
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+
+	for _ in 0..50 {
+		let (start, end) = (format!("Site {}", rng.below(30)), format!("Site {}", rng.below(30)));
+		if !network.contains_campsite(&start) || !network.contains_campsite(&end) {
+			continue
+		}
+		let departure = rng.below(10_000) as u32;
+		let journey = network.earliest_arrival(&start, &end, &hiker, departure, travel_time);
+		let cost = network.optimal_path(&start, &end, &hiker, travel_time);
+		assert_eq!(journey.as_ref().map(|j| j.route.cost), cost);
+		assert_eq!(journey.map(|j| j.arrival), cost.map(|c| departure + c));
+	}
+}
+
+#[test]
+fn reachable_within_1() {
+	let network = TrailNetwork::from_iter(test_path());
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+	let reachable =
+		|budget| network.reachable_within("Green Lake", &Hiker::default(), budget, travel_time);
+
+	assert_eq!(reachable(0), vec![("Green Lake".to_string(), 0)]);
+	assert_eq!(
+		reachable(1200),
+		vec![
+			("Green Lake".to_string(), 0),
+			("B".to_string(), 500),
+			("Prairie Meadow".to_string(), 1200),
+		]
+	);
+	assert_eq!(reachable(u32::MAX).len(), 4);
+	assert!(network
+		.reachable_within("Nowhere", &Hiker::default(), 100, travel_time)
+		.is_empty());
+}
+
+#[test]
+fn reachable_within_matches_optimal_path() {
+	let mut rng = Rng(0x15c);
+	let network = random_network(&mut rng, 30, 90);
+	let hiker = rng.hiker();
+	let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+
+	for budget in [0, 50, 200, 1000] {
+		let reachable = network.reachable_within("Site 0", &hiker, budget, travel_time);
+		assert!(reachable.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+		for campsite in network.campsites() {
+			let cost = network
+				.optimal_path("Site 0", campsite, &hiker, travel_time)
+				.filter(|&cost| cost <= budget);
+			let found = reachable.iter().find(|(name, _)| name == campsite).map(|(_, c)| *c);
+			assert_eq!(found, cost, "{campsite} within {budget}");
+		}
+	}
+}
+
+#[test]
+fn validate_clean_network() {
+	let network: TrailNetwork = "
+		Green Lake <=> Prairie Meadow: 700 (UnpavedTrail) [30]
+		Green Lake => Prairie Meadow: 400 (Zipline) [220]
+	"
+	.parse()
+	.unwrap();
+	let report = network.validate();
+	assert!(report.is_clean(), "{report}");
+	assert!(!report.has_errors());
+}
+
+#[test]
+fn validate_reports_problems() {
+	let network: TrailNetwork = "
+		Green Lake <=> Prairie Meadow: 700 (UnpavedTrail) [30]
+		green  lake => Bird Watch: 100 (PavedTrail) [0]
+		Bird Watch => Green Lake: 100 (PavedTrail) [0]
+		Bird Watch => Green Lake: 100 (PavedTrail) [0]
+		Mountain Top => Green Lake: 2000 (Zipline) [90]
+	"
+	.parse()
+	.unwrap();
+	let report = network.validate();
+	assert!(report.has_errors());
+	assert_eq!(
+		report.problems,
+		vec![
+			Problem::SimilarNames { names: vec!["Green Lake".into(), "green  lake".into()] },
+			Problem::DuplicateTrail {
+				trail: Trail::try_from(
+					"Bird Watch => Green Lake: 100 (PavedTrail) [0]".to_string()
+				)
+				.unwrap(),
+				count: 2,
+			},
+			Problem::NoIncomingTrail { campsite: "green  lake".into() },
+			Problem::NoIncomingTrail { campsite: "Mountain Top".into() },
+			Problem::OneWay {
+				trail: Trail::try_from(
+					"green  lake => Bird Watch: 100 (PavedTrail) [0]".to_string()
+				)
+				.unwrap(),
+			},
+			Problem::OneWay {
+				trail: Trail::try_from(
+					"Bird Watch => Green Lake: 100 (PavedTrail) [0]".to_string()
+				)
+				.unwrap(),
+			},
+			Problem::OneWay {
+				trail: Trail::try_from(
+					"Bird Watch => Green Lake: 100 (PavedTrail) [0]".to_string()
+				)
+				.unwrap(),
+			},
+		]
+	);
+	assert_eq!(
+		report.to_string().lines().next(),
+		Some(
+			r#"error: campsite names differ only in whitespace or case: "Green Lake", "green  lake""#
+		)
+	);
+
+	// Names with stray whitespace cannot be written as trails, but are still quoted in reports.
+	let mut network = TrailNetwork::new();
+	let trail = Trail::try_from("Green Lake => Bird Watch: 100 (PavedTrail) [0]".to_string());
+	let trail = Trail { start: " Green Lake".into(), ..trail.unwrap() };
+	network.add_trail(trail.clone());
+	network.add_trail(trail);
+	network.add_trail(
+		Trail::try_from("Bird Watch => Green Lake: 100 (PavedTrail) [0]".to_string()).unwrap(),
+	);
+	let expected = [
+		r#"error: campsite names differ only in whitespace or case: " Green Lake", "Green Lake""#,
+		r#"error: trail appears 2 times: " Green Lake" => "Bird Watch": 100 (PavedTrail) [0]"#,
+		r#"warning: no trail leads to " Green Lake""#,
+		r#"warning: no trail leaves "Green Lake""#,
+		r#"warning: trail has no way back: " Green Lake" => "Bird Watch": 100 (PavedTrail) [0]"#,
+		r#"warning: trail has no way back: " Green Lake" => "Bird Watch": 100 (PavedTrail) [0]"#,
+		r#"warning: trail has no way back: "Bird Watch" => "Green Lake": 100 (PavedTrail) [0]"#,
+	];
+	assert_eq!(network.validate().to_string().lines().collect::<Vec<_>>(), expected);
+
+	let mut network = TrailNetwork::new();
+	network.set_coordinates("Lost Colony", Coordinates { x: 0.0, y: 0.0 });
+	assert_eq!(
+		network.validate().problems,
+		vec![
+			Problem::DeadEnd { campsite: "Lost Colony".into() },
+			Problem::NoIncomingTrail { campsite: "Lost Colony".into() },
+		]
+	);
+}
+
+#[test]
+fn export_dot() {
+	let network: TrailNetwork = "
+		Green Lake => A: 1000 (RockyTrail) [60]
+		A => Prairie Meadow: 400 (Zipline) [220]
+		Green Lake => Prairie Meadow: 1700 (PavedTrail) [0]
+	"
+	.parse()
+	.unwrap();
+	let route = network.optimal_route("Green Lake", "Prairie Meadow", &Hiker::default(), |_, t| {
+		Some(t.danger as u32)
+	});
+
+	assert_eq!(
+		network.to_dot(route.as_ref()),
+		[
+			"digraph trails {",
+			"\t\"Green Lake\" [style=filled, fillcolor=\"#ffd700\"];",
+			"\t\"A\";",
+			"\t\"Prairie Meadow\" [style=filled, fillcolor=\"#ffd700\"];",
+			"\t\"Green Lake\" -> \"A\" [label=\"1000 (RockyTrail) [60]\", style=\"dotted\", \
+			 color=\"#3cc300\"];",
+			"\t\"A\" -> \"Prairie Meadow\" [label=\"400 (Zipline) [220]\", style=\"bold\", \
+			 color=\"#dc2300\"];",
+			"\t\"Green Lake\" -> \"Prairie Meadow\" [label=\"1700 (PavedTrail) [0]\", \
+			 style=\"solid\", color=\"#00ff00\", penwidth=3];",
+			"}",
+			"",
+		]
+		.join("\n")
+	);
+}
+
+#[test]
+fn export_json() {
+	let mut network: TrailNetwork = r#"Say "Hi"\ => B: 10 (Water) [5]"#.parse().unwrap();
+	network.set_coordinates("B", Coordinates { x: 1.5, y: -2.0 });
+	assert_eq!(
+		network.to_json(None),
+		r#"{"nodes":[{"id":"Say \"Hi\"\\"},{"id":"B","x":1.5,"y":-2}],"edges":[{"from":"Say \"Hi\"\\","to":"B","distance":10,"terrain":"Water","danger":5,"on_route":false}]}"#
+	);
+
+	let route =
+		network.optimal_route(r#"Say "Hi"\"#, "B", &Hiker::default(), |_, t| Some(t.distance));
+	assert!(network.to_json(route.as_ref()).contains(r#""on_route":true"#));
+	assert_eq!(TrailNetwork::new().to_json(None), r#"{"nodes":[],"edges":[]}"#);
+}
+
+/// Checks that the route follows connected trails from `start` to `end` and costs what it says.
+fn assert_valid_route(route: &Route, start: &str, end: &str, cost: impl Fn(&Trail) -> u32) {
+	assert_eq!(route.campsites.first().map(String::as_str), Some(start));
+	assert_eq!(route.campsites.last().map(String::as_str), Some(end));
+	for (trail, pair) in route.trails.iter().zip(route.campsites.windows(2)) {
+		assert_eq!((&trail.start, &trail.end), (&pair[0], &pair[1]));
+	}
+	assert_eq!(route.trails.iter().map(cost).sum::<u32>(), route.cost);
+}
+
+#[test]
+fn bidirectional_matches_dijkstra() {
+	let mut rng = Rng(0xb1d1);
+	for round in 0..20 {
+		let mut network = random_network(&mut rng, 40, 30 + round * 10);
+		let hiker = rng.hiker();
+
+		// Removing trails must keep the backward index in step with the forward one
+		for _ in 0..5 {
+			let index = rng.below(network.trails().len() as u64) as usize;
+			let trail = network.trails()[index].clone();
+			network.remove_trail(&trail);
+		}
+
+		let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+		let danger = |_: &Hiker, t: &Trail| Some(t.danger as u32 % 4);
+		for _ in 0..20 {
+			let start = format!("Site {}", rng.below(40));
+			let end = format!("Site {}", rng.below(40));
+			for cost in [&travel_time as CostFunction, &danger] {
+				let expected = network.optimal_path(&start, &end, &hiker, cost);
+				let route = network.bidirectional_route(&start, &end, &hiker, cost);
+				assert_eq!(route.as_ref().map(|r| r.cost), expected, "{start} to {end}");
+				if let Some(route) = route {
+					assert_valid_route(&route, &start, &end, |t| cost(&hiker, t).unwrap());
+				}
+			}
+		}
+	}
+}
+
+#[test]
+fn bidirectional_on_grid() {
+	let mut rng = Rng(0x9e1d);
+	let network = grid_network(15, &mut rng);
+	let hiker = Hiker::default();
+	for _ in 0..20 {
+		let start = format!("{},{}", rng.below(15), rng.below(15));
+		let end = format!("{},{}", rng.below(15), rng.below(15));
+		assert_eq!(
+			network.bidirectional_path(&start, &end, &hiker, |_, t| Some(t.distance)),
+			network.optimal_path(&start, &end, &hiker, |_, t| Some(t.distance))
+		);
+	}
+}
+
+#[test]
+fn contraction_hierarchy_matches_dijkstra() {
+	let mut rng = Rng(0xc4);
+	for round in 0..5 {
+		let network = random_network(&mut rng, 40, 60 + round * 30);
+		let hiker = rng.hiker();
+		let travel_time = |h: &Hiker, t: &Trail| h.travel_time(&t.terrain, t.distance);
+		let hierarchy = network.contraction_hierarchy(&hiker, travel_time);
+
+		for from in network.campsites() {
+			for to in network.campsites() {
+				assert_eq!(
+					hierarchy.cost(from, to),
+					network.optimal_path(from, to, &hiker, travel_time),
+					"{from} to {to}"
+				);
+			}
+		}
+	}
+
+	let mut rng = Rng(0xc5);
+	let network = grid_network(10, &mut rng);
+	let hierarchy = network.contraction_hierarchy(&Hiker::default(), |_, t| Some(t.distance));
+	for _ in 0..50 {
+		let from = format!("{},{}", rng.below(10), rng.below(10));
+		let to = format!("{},{}", rng.below(10), rng.below(10));
+		assert_eq!(
+			hierarchy.cost(&from, &to),
+			network.optimal_path(&from, &to, &Hiker::default(), |_, t| Some(t.distance))
+		);
+	}
+	assert_eq!(hierarchy.cost("0,0", "Nowhere"), None);
+}
+
+#[test]
+fn contraction_hierarchy_round_trip() {
+	let mut rng = Rng(0xc6);
+	let network = random_network(&mut rng, 20, 50);
+	let hierarchy = network.contraction_hierarchy(&Hiker::default(), |_, t| Some(t.distance));
+
+	let saved = hierarchy.to_string();
+	let loaded: ContractionHierarchy = saved.parse().unwrap();
+	assert_eq!(loaded, hierarchy);
+	assert_eq!(loaded.to_string(), saved);
+	assert_eq!(loaded.campsites().len(), network.campsite_count());
+}
+
+#[test]
+fn contraction_hierarchy_malformed_lines() {
+	let document = "campsite 0 Green Lake\ncampsite x B\nedge 0 7 10\nedge 0 0\nshortcut 0 1 2\n";
+	match document.parse::<ContractionHierarchy>() {
+		Err(LoadError::Malformed(lines)) => assert_eq!(
+			lines.iter().map(|l| l.error.clone()).collect::<Vec<_>>(),
+			vec![
+				ParseError::InvalidNumber { offset: 9, value: "x".into() },
+				ParseError::InvalidNumber { offset: 7, value: "7".into() },
+				ParseError::MissingDelimiter { offset: 8, delimiter: " " },
+				ParseError::UnknownRecord { offset: 0, kind: "shortcut".into() },
+			]
+		),
+		other => panic!("expected malformed lines, got {other:?}"),
+	}
+}
+
+/// A scenic score: the distance, less a reward for the danger, which stands in for the view.
+fn scenic(_: &Hiker, trail: &Trail) -> Option<i64> {
+	Some(trail.distance as i64 - trail.danger as i64)
+}
+
+#[test]
+fn signed_route_with_negative_trails() {
+	let network: TrailNetwork = "
+		Green Lake => Prairie Meadow: 50 (PavedTrail) [0]
+		Green Lake => Lookout: 20 (RockyTrail) [0]
+		Lookout => Prairie Meadow: 10 (RockyTrail) [100]
+	"
+	.parse()
+	.unwrap();
+	let route = network
+		.signed_route("Green Lake", "Prairie Meadow", &Hiker::default(), scenic)
+		.unwrap()
+		.unwrap();
+	assert_eq!(route.cost, -70);
+	assert_eq!(route.campsites, vec!["Green Lake", "Lookout", "Prairie Meadow"]);
+
+	assert_eq!(
+		network.signed_path("Prairie Meadow", "Green Lake", &Hiker::default(), scenic),
+		Ok(None)
+	);
+	assert_eq!(network.signed_path("Lookout", "Lookout", &Hiker::default(), scenic), Ok(Some(0)));
+	assert_eq!(network.signed_path("Nowhere", "Nowhere", &Hiker::default(), scenic), Ok(Some(0)));
+}
+
+#[test]
+fn signed_route_negative_cycle() {
+	let network: TrailNetwork = "
+		Green Lake => Lookout: 20 (RockyTrail) [0]
+		Lookout => Waterfall: 10 (RockyTrail) [50]
+		Waterfall => Lookout: 10 (RockyTrail) [0]
+		Waterfall => Prairie Meadow: 10 (PavedTrail) [0]
+		Green Lake => Bird Watch: 10 (PavedTrail) [0]
+	"
+	.parse()
+	.unwrap();
+
+	let error = network
+		.signed_route("Green Lake", "Prairie Meadow", &Hiker::default(), scenic)
+		.unwrap_err();
+	assert_eq!(error.cost, -30);
+	assert_eq!(error.trails.len(), 2);
+	assert_eq!(error.campsites.first(), error.campsites.last());
+	assert!(error.campsites.contains(&"Lookout".to_string()));
+	assert!(error.campsites.contains(&"Waterfall".to_string()));
+	assert!(error.to_string().starts_with("negative cycle with a cost of -30: "));
+
+	// A cycle that does not lead to the destination does not matter.
+	assert_eq!(
+		network.signed_path("Green Lake", "Bird Watch", &Hiker::default(), scenic),
+		Ok(Some(10))
+	);
+}
+
+#[test]
+fn signed_route_matches_dijkstra_with_potentials() {
+	// Shifting every trail's cost by the difference of a potential at its ends changes the cost of
+	// every route between the same campsites by the same amount, and never creates negative cycles.
+	let mut rng = Rng(0x516);
+	let network = random_network(&mut rng, 30, 100);
+	let potentials: std::collections::HashMap<String, i64> = network
+		.campsites()
+		.map(|campsite| (campsite.to_string(), rng.below(200) as i64))
+		.collect();
+	let shifted =
+		|_: &Hiker, t: &Trail| Some(t.distance as i64 + potentials[&t.start] - potentials[&t.end]);
+	assert!(network.trails().iter().any(|t| shifted(&Hiker::default(), t).unwrap() < 0));
+
+	for _ in 0..50 {
+		let (start, end) = (format!("Site {}", rng.below(30)), format!("Site {}", rng.below(30)));
+		if !network.contains_campsite(&start) || !network.contains_campsite(&end) {
+			continue
+		}
+		let expected = network
+			.optimal_path(&start, &end, &Hiker::default(), |_, t| Some(t.distance))
+			.map(|cost| cost as i64 + potentials[&start] - potentials[&end]);
+		assert_eq!(network.signed_path(&start, &end, &Hiker::default(), shifted), Ok(expected));
+		assert_eq!(
+			network.signed_path(&start, &end, &Hiker::default(), |_, t| Some(t.distance as i64)),
+			Ok(expected.map(|cost| cost - potentials[&start] + potentials[&end]))
+		);
+	}
+}
+
+fn costly_network() -> TrailNetwork {
+	"Green Lake => Lookout: 4000000000 (PavedTrail) [0]\n\
+	 Lookout => Prairie Meadows: 4000000000 (PavedTrail) [0]"
+		.parse()
+		.unwrap()
+}
+
+#[test]
+fn overflowing_routes_are_not_returned() {
+	let network = costly_network();
+	let bill = Hiker::default();
+	let distance = |_: &Hiker, t: &Trail| Some(t.distance);
+	let (start, end) = ("Green Lake", "Prairie Meadows");
+
+	assert_eq!(
+		optimal_path(start.into(), end.into(), &bill, network.trails().iter().cloned(), distance),
+		None
+	);
+	assert_eq!(
+		network.astar_route(start, end, &bill, distance, &|_: &Coordinates, _: &Coordinates| 0),
+		None
+	);
+	assert_eq!(network.bidirectional_path(start, end, &bill, distance), None);
+	assert_eq!(network.k_shortest_routes(start, end, &bill, 3, distance), Vec::new());
+	assert_eq!(network.pareto_routes(start, end, &bill, &[&distance]), Vec::new());
+	assert_eq!(
+		network.plan_itinerary(start, &["Lookout"], end, WaypointOrder::Any, &bill, distance),
+		None
+	);
+	for algorithm in [AllPairsAlgorithm::FloydWarshall, AllPairsAlgorithm::RepeatedDijkstra] {
+		let matrix = network.all_pairs_costs_using(algorithm, &bill, distance);
+		assert_eq!(matrix.cost(start, "Lookout"), Some(4000000000));
+		assert_eq!(matrix.cost(start, end), None);
+	}
+
+	// A route that does fit is still found, even when a costlier one overflows.
+	let mut network = network;
+	network.add_trail(Trail {
+		start: "Green Lake".into(),
+		end: "Prairie Meadows".into(),
+		distance: u32::MAX,
+		terrain: Terrain::PavedTrail,
+		danger: 0,
+	});
+	assert_eq!(network.optimal_path(start, end, &bill, distance), Some(u32::MAX));
+	assert_eq!(network.bidirectional_path(start, end, &bill, distance), Some(u32::MAX));
+}
+
+#[test]
+fn overflowing_routes_with_wider_costs() {
+	let network = costly_network();
+	let bill = Hiker::default();
+	let (start, end) = ("Green Lake", "Prairie Meadows");
+
+	let error = network
+		.try_optimal_path(start, end, &bill, |_, t| Some(t.distance))
+		.unwrap_err();
+	assert_eq!(error, CostOverflow { start: start.into(), destination: end.into() });
+	assert_eq!(
+		error.to_string(),
+		"every route from `Green Lake` to `Prairie Meadows` costs more than the cost type can represent"
+	);
+
+	let route = network
+		.try_optimal_route(start, end, &bill, |_, t| Some(t.distance as u64))
+		.unwrap()
+		.unwrap();
+	assert_eq!(route.cost, 8000000000);
+	assert_eq!(route.campsites, ["Green Lake", "Lookout", "Prairie Meadows"]);
+	assert_eq!(
+		try_optimal_path(
+			start.into(),
+			end.into(),
+			&bill,
+			network.trails().iter().cloned(),
+			|_, t| { Some(t.distance as u64) }
+		),
+		Ok(Some(8000000000))
+	);
+
+	// Unreachable destinations are not mistaken for overflows.
+	assert_eq!(network.try_optimal_path(end, start, &bill, |_, t| Some(t.distance)), Ok(None));
+	assert_eq!(network.try_optimal_path(start, end, &bill, |_, _| None::<u32>), Ok(None));
+}
+
+#[test]
+fn explain_route() {
+	let network: TrailNetwork = "Green Lake => Prairie Meadows: 1000 (Zipline) [90]\n\
+	                             Green Lake => Lookout: 100 (PavedTrail) [10]\n\
+	                             Green Lake => Cliff: 10 (RockyTrail) [200]\n\
+	                             Lookout => Prairie Meadows: 200 (UnpavedTrail) [20]"
+		.parse()
+		.unwrap();
+	let cautious = |hiker: &Hiker, t: &Trail| {
+		hiker.travel_time(&t.terrain, t.distance).filter(|_| t.danger < 100)
+	};
+
+	let explanation = network
+		.explain_route("Green Lake", "Prairie Meadows", &Hiker::default(), cautious)
+		.unwrap();
+	assert_eq!(explanation.route.as_ref().map(|route| route.cost), Some(250));
+	let breakdown: Vec<_> = explanation
+		.trails
+		.iter()
+		.map(|b| (b.trail.terrain, b.trail.distance, b.trail.danger, b.travel_time, b.cost))
+		.collect();
+	assert_eq!(
+		breakdown,
+		[(Terrain::PavedTrail, 100, 10, 50, 50), (Terrain::UnpavedTrail, 200, 20, 200, 200)]
+	);
+	let infeasible: Vec<_> = explanation
+		.infeasible
+		.iter()
+		.map(|rejected| (rejected.trail.end.as_str(), rejected.reasons.clone()))
+		.collect();
+	assert_eq!(infeasible, [("Prairie Meadows", vec![Rejection::NotBrave])]);
+	let excluded: Vec<_> = explanation.excluded.iter().map(|t| t.end.as_str()).collect();
+	assert_eq!(excluded, ["Cliff"]);
+	assert_eq!(
+		explanation.to_string(),
+		"route costs 250:\n\
+		 \x20 \"Green Lake\" => \"Lookout\": 100 (PavedTrail) [10], travel time 50, cost 50\n\
+		 \x20 \"Lookout\" => \"Prairie Meadows\": 200 (UnpavedTrail) [20], travel time 200, cost \
+		 200\n\
+		 cannot take \"Green Lake\" => \"Prairie Meadows\": 1000 (Zipline) [90]: not brave\n\
+		 excluded by the cost function: \"Green Lake\" => \"Cliff\": 10 (RockyTrail) [200]\n"
+	);
+
+	// Feasibility does not depend on the cost function, so a zipline that is the shortest way is
+	// still not taken by a hiker who is not brave.
+	let distance = |_: &Hiker, t: &Trail| Some(t.distance / 10);
+	let explanation = network
+		.explain_route("Green Lake", "Prairie Meadows", &Hiker::default(), distance)
+		.unwrap();
+	assert_eq!(explanation.route.map(|route| route.cost), Some(30));
+	assert_eq!(explanation.infeasible.len(), 1);
+	assert_eq!(explanation.excluded, []);
+
+	// A hiker who is neither brave nor strong is told both, and a brave and strong one flies.
+	let timid = Hiker { strong: false, brave: false, ..Hiker::default() };
+	let explanation = network
+		.explain_route("Green Lake", "Prairie Meadows", &timid, cautious)
+		.unwrap();
+	assert_eq!(explanation.infeasible[0].reasons, [Rejection::NotBrave, Rejection::NotStrong]);
+	let daredevil = Hiker { brave: true, ..Hiker::default() };
+	let explanation = network
+		.explain_route("Green Lake", "Prairie Meadows", &daredevil, cautious)
+		.unwrap();
+	assert_eq!(explanation.route.map(|route| route.cost), Some(100));
+	assert_eq!(explanation.trails[0].travel_time, 100);
+
+	// Travel times and feasibility follow the given profile.
+	let profile: TerrainProfile = "PavedTrail: 3\nUnpavedTrail: 1".parse().unwrap();
+	let explanation = network
+		.explain_route_with(&profile, "Green Lake", "Prairie Meadows", &Hiker::default(), distance)
+		.unwrap();
+	assert_eq!(explanation.trails.iter().map(|b| b.travel_time).collect::<Vec<_>>(), [300, 200]);
+	let infeasible: Vec<_> =
+		explanation.infeasible.iter().map(|rejected| rejected.reasons.clone()).collect();
+	assert_eq!(infeasible, [vec![Rejection::NotBrave, Rejection::NoRate], vec![Rejection::NoRate]]);
+
+	// Unknown starts cannot be explained, but unreachable destinations still list the rejections.
+	assert_eq!(network.explain_route("Nowhere", "Cliff", &timid, cautious), None);
+	let explanation = network.explain_route("Green Lake", "Nowhere", &timid, cautious).unwrap();
+	assert_eq!(
+		(explanation.route, explanation.trails.len(), explanation.infeasible.len()),
+		(None, 0, 1)
+	);
+	assert_eq!(explanation.excluded.len(), 1);
+}
+
+#[test]
+fn overflowing_travel_times() {
+	let network: TrailNetwork = "A => B: 4000000000 (Water) [0]".parse().unwrap();
+	let swimmer = Hiker { swimming: Skill::Beginner, ..Hiker::default() };
+
+	assert_eq!(swimmer.travel_time(&Terrain::Water, 4000000000), None);
+	assert_eq!(swimmer.travel_time_u64(&Terrain::Water, 4000000000), Some(36000000000));
+	assert_eq!(swimmer.travel_time(&Terrain::Water, 400000000), Some(3600000000));
+	assert_eq!(Rate::new(1, 0).apply_u64(10), None);
+
+	assert_eq!(
+		network.optimal_path("A", "B", &swimmer, |h, t| h.travel_time(&t.terrain, t.distance)),
+		None
+	);
+	assert_eq!(
+		network
+			.try_optimal_path("A", "B", &swimmer, |h, t| h.travel_time_u64(&t.terrain, t.distance)),
+		Ok(Some(36000000000))
+	);
+}